@@ -0,0 +1,414 @@
+// local referee for https://www.codingame.com/multiplayer/bot-programming/back-to-the-code
+//
+// usage: back-to-the-code-referee [--seed N] [--rounds N] [--size WxH] [--verbose] <bot> <bot> [<bot> <bot>]
+//
+// a bot is either a command (quoted if it has arguments) talking over stdin/stdout,
+// or a scripted bot: `scripted:idle` or `scripted:random`
+// bots can not read the board size from their inputs, give it to them too with `--size`:
+// back-to-the-code-referee --size 12x8 "target/release/back-to-the-code --size 12x8" scripted:random
+//
+// rules implemented here:
+// - each round every player gives a target (`x y`) and moves one cell toward it, along the x axis first
+// - a player entering a neutral cell takes it, two players entering the same neutral cell leave it neutral
+// - a neutral zone surrounded by a player cells (not touching the border, without another player inside) is captured
+//   (the rules of `src/territory.rs`, the bot simulates rounds with them too)
+// - `BACK n` (once per game, 1 to 25 rounds) restores the whole board n rounds ago, the round counter keeps going
+// - the game ends after 350 rounds or when every cell is owned, the score is the number of owned cells
+
+use codinggame_backtocode_rust::process::BotProcess;
+use codinggame_backtocode_rust::random::Random;
+use codinggame_backtocode_rust::territory;
+use std::env;
+
+const DEFAULT_WIDTH: i32 = 35;
+const DEFAULT_HEIGHT: i32 = 20;
+const MAX_ROUNDS: i32 = 350;
+const MAX_BACK_IN_TIME: i32 = 25;
+
+trait Agent {
+    /// sends lines to the bot
+    fn send(&mut self, lines: &[String]);
+    /// reads the action of the turn, `None` if the bot died
+    fn receive(&mut self) -> Option<String>;
+}
+
+impl Agent for BotProcess {
+    fn send(&mut self, lines: &[String]) {
+        BotProcess::send(self, lines);
+    }
+
+    fn receive(&mut self) -> Option<String> {
+        BotProcess::receive(self)
+    }
+}
+
+#[derive(PartialEq)]
+enum ScriptedKind {
+    /// never moves
+    Idle,
+    /// goes to a random cell, then picks another one
+    Random,
+}
+
+/// in process bots, they read the same lines as a real bot
+struct ScriptedAgent {
+    kind: ScriptedKind,
+    random: Random,
+    width: i32,
+    height: i32,
+    position: [i32; 2],
+    target: Option<[i32; 2]>,
+}
+
+impl ScriptedAgent {
+    pub fn new(name: &str, seed: u64, width: i32, height: i32) -> Self {
+        let kind = match name {
+            "idle" => ScriptedKind::Idle,
+            "random" => ScriptedKind::Random,
+            _ => panic!("unknown scripted bot: {}", name),
+        };
+        Self {
+            kind,
+            random: Random::new(seed),
+            width,
+            height,
+            position: [0, 0],
+            target: None,
+        }
+    }
+}
+
+impl Agent for ScriptedAgent {
+    fn send(&mut self, lines: &[String]) {
+        // a turn is: round, our position, opponents positions, grid
+        // the first message (opponent count) is a single line
+        let Some(line) = lines.get(1) else {
+            return;
+        };
+        let inputs = line.split(' ').collect::<Vec<_>>();
+        self.position = [inputs[0].parse().unwrap(), inputs[1].parse().unwrap()];
+    }
+
+    fn receive(&mut self) -> Option<String> {
+        if self.kind == ScriptedKind::Idle {
+            return Some(format!("{} {}", self.position[0], self.position[1]));
+        }
+        if self.target.is_none() || self.target == Some(self.position) {
+            self.target = Some([
+                self.random.range(0, self.width),
+                self.random.range(0, self.height),
+            ]);
+        }
+        let target = self.target.unwrap();
+        Some(format!("{} {}", target[0], target[1]))
+    }
+}
+
+/// everything that is restored with `BACK`
+#[derive(Clone, PartialEq, Debug)]
+struct State {
+    /// player_id owning each cell (indexed by y * width + x), -1 if neutral
+    owners: Vec<i32>,
+    positions: Vec<[i32; 2]>,
+}
+
+enum Action {
+    Move([i32; 2]),
+    BackInTime(i32),
+}
+
+impl Action {
+    fn decode(str: &str) -> Option<Self> {
+        let inputs = str.split_whitespace().collect::<Vec<_>>();
+        if inputs.first() == Some(&"BACK") {
+            return Some(Action::BackInTime(inputs.get(1)?.parse().ok()?));
+        }
+        let x = inputs.first()?.parse().ok()?;
+        let y = inputs.get(1)?.parse().ok()?;
+        Some(Action::Move([x, y]))
+    }
+}
+
+struct Referee {
+    width: i32,
+    height: i32,
+    round: i32,
+    max_rounds: i32,
+    state: State,
+    /// one state per round, the oldest first, used to go back in time
+    history: Vec<State>,
+    back_in_time_left: Vec<i32>,
+    alive: Vec<bool>,
+}
+
+impl Referee {
+    pub fn new(width: i32, height: i32, players_count: usize, max_rounds: i32, seed: u64) -> Self {
+        let mut random = Random::new(seed);
+        let mut positions: Vec<[i32; 2]> = Vec::with_capacity(players_count);
+        while positions.len() < players_count {
+            let position = [random.range(0, width), random.range(0, height)];
+            if !positions.contains(&position) {
+                positions.push(position);
+            }
+        }
+        let mut referee = Self {
+            width,
+            height,
+            round: 0,
+            max_rounds,
+            state: State {
+                owners: vec![-1; (width * height) as usize],
+                positions,
+            },
+            history: Vec::new(),
+            back_in_time_left: vec![1; players_count],
+            alive: vec![true; players_count],
+        };
+        referee.claim_cells();
+        referee
+    }
+
+    pub fn get_cell_index(&self, position: [i32; 2]) -> usize {
+        (position[1] * self.width + position[0]) as usize
+    }
+
+    /// players take the neutral cell they are on (see `territory::claim_cells`)
+    fn claim_cells(&mut self) {
+        territory::claim_cells(self.width, &mut self.state.owners, &self.state.positions);
+    }
+
+    /// enclosed neutral zones go to the player around them (see `territory::get_captured_cells`)
+    fn capture_cells(&mut self) {
+        territory::capture_cells(
+            self.width,
+            self.height,
+            &mut self.state.owners,
+            &self.state.positions,
+        );
+    }
+
+    /// the lines a player reads this round, every player sees itself as player 0
+    pub fn get_turn_lines(&self, player_id: usize) -> Vec<String> {
+        let players_count = self.state.positions.len();
+        // player ids as seen by this player: itself first, then the others in order
+        let order = std::iter::once(player_id)
+            .chain((0..players_count).filter(|id| *id != player_id))
+            .collect::<Vec<_>>();
+        let mut local_ids = vec![0; players_count];
+        for (local_id, id) in order.iter().enumerate() {
+            local_ids[*id] = local_id;
+        }
+
+        let mut lines = vec![self.round.to_string()];
+        for id in &order {
+            let position = self.state.positions[*id];
+            lines.push(format!(
+                "{} {} {}",
+                position[0], position[1], self.back_in_time_left[*id]
+            ));
+        }
+        for y in 0..self.height {
+            let mut line = String::with_capacity(self.width as usize);
+            for x in 0..self.width {
+                let owner = self.state.owners[self.get_cell_index([x, y])];
+                if owner < 0 {
+                    line.push('.');
+                } else {
+                    line.push_str(&local_ids[owner as usize].to_string());
+                }
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// moves one cell toward the target, along the x axis first
+    fn get_next_position(from: [i32; 2], to: [i32; 2]) -> [i32; 2] {
+        if from[0] != to[0] {
+            return [from[0] + (to[0] - from[0]).signum(), from[1]];
+        }
+        [from[0], from[1] + (to[1] - from[1]).signum()]
+    }
+
+    /// plays a round, returns false once the game is over
+    pub fn play_round(&mut self, agents: &mut [Box<dyn Agent>]) -> bool {
+        self.round += 1;
+        self.history.push(self.state.clone());
+        if self.history.len() > MAX_BACK_IN_TIME as usize + 1 {
+            self.history.remove(0);
+        }
+
+        let mut actions = Vec::with_capacity(agents.len());
+        for (player_id, agent) in agents.iter_mut().enumerate() {
+            if !self.alive[player_id] {
+                actions.push(None);
+                continue;
+            }
+            agent.send(&self.get_turn_lines(player_id));
+            let action = agent.receive();
+            if action.is_none() {
+                eprintln!("player {} died at round {}", player_id, self.round);
+                self.alive[player_id] = false;
+            }
+            actions.push(action.and_then(|str| Action::decode(&str)));
+        }
+
+        // going back in time cancels every move of the round
+        let mut rounds_back = 0;
+        for (player_id, action) in actions.iter().enumerate() {
+            if let Some(Action::BackInTime(n)) = action {
+                // history holds the current state as its last element
+                let max = (self.history.len() as i32 - 1).min(MAX_BACK_IN_TIME);
+                if self.back_in_time_left[player_id] > 0 && (1..=max).contains(n) {
+                    self.back_in_time_left[player_id] -= 1;
+                    rounds_back = rounds_back.max(*n);
+                } else {
+                    eprintln!("player {} can not go back {} rounds", player_id, n);
+                }
+            }
+        }
+        if rounds_back > 0 {
+            let len = self.history.len() - rounds_back as usize;
+            self.state = self.history[len - 1].clone();
+            self.history.truncate(len - 1);
+            return self.round < self.max_rounds;
+        }
+
+        for (player_id, action) in actions.iter().enumerate() {
+            if let Some(Action::Move(target)) = action {
+                let target = [
+                    target[0].clamp(0, self.width - 1),
+                    target[1].clamp(0, self.height - 1),
+                ];
+                let position = self.state.positions[player_id];
+                self.state.positions[player_id] = Referee::get_next_position(position, target);
+            }
+        }
+        self.claim_cells();
+        self.capture_cells();
+
+        let is_full = self.state.owners.iter().all(|owner| *owner >= 0);
+        !is_full && self.round < self.max_rounds && self.alive.iter().any(|alive| *alive)
+    }
+
+    pub fn get_scores(&self) -> Vec<i32> {
+        (0..self.state.positions.len() as i32)
+            .map(|player_id| {
+                self.state
+                    .owners
+                    .iter()
+                    .filter(|owner| **owner == player_id)
+                    .count() as i32
+            })
+            .collect()
+    }
+}
+
+fn main() {
+    let mut seed = 0;
+    let mut max_rounds = MAX_ROUNDS;
+    let mut width = DEFAULT_WIDTH;
+    let mut height = DEFAULT_HEIGHT;
+    let mut verbose = false;
+    let mut bots = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = args.next().expect("--seed needs a value").parse().unwrap(),
+            "--rounds" => {
+                max_rounds = args
+                    .next()
+                    .expect("--rounds needs a value")
+                    .parse()
+                    .unwrap()
+            }
+            "--size" => {
+                let size = args.next().expect("--size needs a value (WxH)");
+                let (w, h) = size.split_once('x').expect("--size is WxH");
+                width = w.parse().unwrap();
+                height = h.parse().unwrap();
+            }
+            "--verbose" => verbose = true,
+            _ => bots.push(arg),
+        }
+    }
+    if !(2..=4).contains(&bots.len()) {
+        eprintln!("usage: back-to-the-code-referee [--seed N] [--rounds N] [--size WxH] [--verbose] <bot> <bot> [<bot> <bot>]");
+        eprintln!("a bot is a command or scripted:idle / scripted:random");
+        std::process::exit(2);
+    }
+
+    let mut agents: Vec<Box<dyn Agent>> = bots
+        .iter()
+        .enumerate()
+        .map(|(player_id, bot)| -> Box<dyn Agent> {
+            match bot.strip_prefix("scripted:") {
+                Some(name) => Box::new(ScriptedAgent::new(
+                    name,
+                    seed + player_id as u64,
+                    width,
+                    height,
+                )),
+                None => Box::new(BotProcess::spawn(bot, verbose)),
+            }
+        })
+        .collect();
+    for agent in agents.iter_mut() {
+        agent.send(&[(bots.len() - 1).to_string()]);
+    }
+
+    let mut referee = Referee::new(width, height, bots.len(), max_rounds, seed);
+    while referee.play_round(&mut agents) {}
+
+    eprintln!("game over after {} rounds", referee.round);
+    for (player_id, score) in referee.get_scores().iter().enumerate() {
+        println!("player {} ({}): {}", player_id, bots[player_id], score);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// gives the answers in order, then dies
+    struct ListAgent {
+        answers: VecDeque<String>,
+    }
+
+    impl Agent for ListAgent {
+        fn send(&mut self, _lines: &[String]) {}
+
+        fn receive(&mut self) -> Option<String> {
+            self.answers.pop_front()
+        }
+    }
+
+    #[test]
+    fn back_in_time_restores_the_board_and_the_players() {
+        let answers = ["7 0", "7 0", "7 0", "7 5", "7 5", "7 5", "BACK 4"];
+        let mut agents: Vec<Box<dyn Agent>> = vec![
+            Box::new(ListAgent {
+                answers: answers.iter().map(|answer| answer.to_string()).collect(),
+            }),
+            Box::new(ScriptedAgent::new("random", 2, 8, 6)),
+        ];
+        let mut referee = Referee::new(8, 6, 2, MAX_ROUNDS, 1);
+        for _ in 0..2 {
+            referee.play_round(&mut agents);
+        }
+        let saved = referee.state.clone();
+        for _ in 0..4 {
+            referee.play_round(&mut agents);
+        }
+        assert_ne!(referee.state, saved);
+
+        referee.play_round(&mut agents);
+        assert_eq!(referee.state, saved);
+        assert_eq!(referee.round, 7);
+        assert_eq!(referee.back_in_time_left, vec![0, 1]);
+        // the history before the restored round is kept, once
+        assert_eq!(referee.history.len(), 2);
+    }
+}
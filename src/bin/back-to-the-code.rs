@@ -2,15 +2,32 @@
 // 2527
 // 1411
 // 531
-use std::collections::{HashMap, HashSet};
+use codinggame_backtocode_rust::params;
+use codinggame_backtocode_rust::territory;
+use codinggame_backtocode_rust::timer::{self, TurnTimer};
+use codinggame_backtocode_rust::{debug, info, trace, transcript, warn, write_answer};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::env;
 use std::fmt::{self, Debug};
 use std::hash::Hash;
-use std::io;
+use std::time::Duration;
 
-const HEIGHT: i32 = 20;
-const WIDTH: i32 = 35;
-const MAX_DISTANCE: f64 = 1625.;
-const STARTING_SQUARE_WIDTH: i32 = 18;
+/// the board size on CodinGame, override it with `--size WxH`
+const DEFAULT_WIDTH: i32 = 35;
+const DEFAULT_HEIGHT: i32 = 20;
+/// a player can go back up to this number of rounds (once per game)
+const MAX_BACK_IN_TIME: i32 = 25;
+/// rewinding must win at least this number of cells compared to continuing
+const BACK_IN_TIME_MIN_GAIN: i32 = 5;
+/// number of best rectangles we check the safety of before falling back to the best one
+const MAX_SAFETY_CHECKS: usize = 50;
+/// a step to reach a rectangle costs this percent of a round spent drawing its perimeter
+const DISTANCE_WEIGHT: i32 = 100;
+/// the cells won are raised to this power (in percent) in the scores, above 100 the big rectangles come first
+const AREA_EXPONENT: i32 = 100;
+/// time kept to finish the turn once a search stops
+const TIME_MARGIN: Duration = Duration::from_millis(15);
 
 // closest possible to enemies
 // to the cell I can get without them getting it before
@@ -64,6 +81,10 @@ struct Cell {
      * distance is -1 if the cell is owned by a player
      */
     pub distances: HashMap<i32, f64>,
+    /**
+     * contains a map player_id -> number of moves for the player to reach this cell
+     */
+    pub steps: HashMap<i32, i32>,
 }
 
 impl PartialEq for Cell {
@@ -90,6 +111,7 @@ impl Default for Cell {
             player_id: -1,
             debug_char: char::default(),
             distances: HashMap::new(),
+            steps: HashMap::new(),
         }
     }
 }
@@ -115,13 +137,11 @@ impl Cell {
     pub fn distance_point(&self, x: i32, y: i32) -> f64 {
         f64::from(self.x - x).powi(2) + f64::from(self.y - y).powi(2)
     }
-
-    pub fn is_at(&self, other: &Cell) -> bool {
-        self.x == other.x && self.y == other.y
-    }
 }
 
 struct Grid {
+    pub width: i32,
+    pub height: i32,
     pub cells: Vec<Cell>,
 }
 
@@ -130,7 +150,7 @@ impl fmt::Display for Grid {
         let mut last_y: i32 = 0;
         for cell in self.cells.iter() {
             if cell.y != last_y {
-                write!(f, "\n").unwrap();
+                writeln!(f).unwrap();
             }
             last_y = cell.y;
             if cell.debug_char != char::default() {
@@ -141,20 +161,22 @@ impl fmt::Display for Grid {
                 write!(f, "{}", cell.player_id).unwrap();
             };
         }
-        write!(f, "\n")
+        writeln!(f)
     }
 }
 
-impl Default for Grid {
-    fn default() -> Self {
+impl Grid {
+    pub fn new(width: i32, height: i32) -> Self {
         let mut grid = Grid {
-            cells: Vec::<Cell>::with_capacity((HEIGHT * WIDTH) as usize),
+            width,
+            height,
+            cells: Vec::<Cell>::with_capacity((height * width) as usize),
         };
-        for y in 0..HEIGHT {
-            for x in 0..WIDTH {
+        for y in 0..height {
+            for x in 0..width {
                 grid.cells.push(Cell {
-                    x: x,
-                    y: y,
+                    x,
+                    y,
                     player_id: -1,
                     ..Default::default()
                 })
@@ -162,24 +184,33 @@ impl Default for Grid {
         }
         grid
     }
-}
 
-impl Grid {
-    pub fn get_cell_index(x: i32, y: i32) -> usize {
-        (y * WIDTH + x) as usize
+    pub fn get_cell_index(&self, x: i32, y: i32) -> usize {
+        (y * self.width + x) as usize
     }
+
+    pub fn is_in_bounds(&self, x: i32, y: i32) -> bool {
+        (0..self.width).contains(&x) && (0..self.height).contains(&y)
+    }
+
     pub fn get_cell(&self, x: i32, y: i32) -> Option<&Cell> {
-        if x < 0 || x >= WIDTH || y < 0 || y >= HEIGHT {
+        if !self.is_in_bounds(x, y) {
             return None;
         }
-        self.cells.get(Grid::get_cell_index(x, y))
+        self.cells.get(self.get_cell_index(x, y))
     }
 
     pub fn get_cell_mut(&mut self, x: i32, y: i32) -> Option<&mut Cell> {
-        if x < 0 || x >= WIDTH || y < 0 || y >= HEIGHT {
+        if !self.is_in_bounds(x, y) {
             return None;
         }
-        self.cells.get_mut(Grid::get_cell_index(x, y))
+        let index = self.get_cell_index(x, y);
+        self.cells.get_mut(index)
+    }
+
+    /// the biggest (squared) distance between two cells of the grid
+    pub fn get_max_distance(&self) -> f64 {
+        f64::from(self.width.pow(2) + self.height.pow(2))
     }
 
     pub fn compute_all_distances_player(&mut self, player_id: i32, x: i32, y: i32) {
@@ -192,7 +223,32 @@ impl Grid {
         }
     }
 
+    /// bfs from the player position, a player moves one cell per round over the 4 directions
+    pub fn compute_all_steps_player(&mut self, player_id: i32, x: i32, y: i32) {
+        for cell in self.cells.iter_mut() {
+            cell.steps.remove(&player_id);
+        }
+        let mut queue = VecDeque::new();
+        if let Some(cell) = self.get_cell_mut(x, y) {
+            cell.steps.insert(player_id, 0);
+            queue.push_back((x, y, 0));
+        }
+        while let Some((x, y, steps)) = queue.pop_front() {
+            for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                let Some(cell) = self.get_cell_mut(nx, ny) else {
+                    continue;
+                };
+                if cell.steps.contains_key(&player_id) {
+                    continue;
+                }
+                cell.steps.insert(player_id, steps + 1);
+                queue.push_back((nx, ny, steps + 1));
+            }
+        }
+    }
+
     pub fn debug_distances(&self, player_id: i32) -> String {
+        let max_distance = self.get_max_distance();
         let mut str = String::from("");
         let mut last_y: i32 = 0;
         for cell in self.cells.iter() {
@@ -208,7 +264,7 @@ impl Grid {
                     .map_or(String::from("."), |v| if v < &0. {
                         String::from("x")
                     } else {
-                        format!("{:.0}", ((v / MAX_DISTANCE) * 10.).round())
+                        format!("{:.0}", ((v / max_distance) * 10.).round())
                     })
             );
         }
@@ -229,12 +285,10 @@ impl Grid {
         y: i32,
         player_id: i32,
     ) -> Option<Vec<Cell>> {
-        let mut perimeter = HashSet::<Cell>::new(); // TODO: not sure we need hashset because we should not pick twice the same cell, but was lazy
+        let mut perimeter = Vec::<Cell>::with_capacity((2 * (w + h)) as usize);
         for relative_x in 0..w {
             for relative_y in 0..h {
-                let Some(cell) = self.get_cell(relative_x + x, relative_y + y) else {
-                    return None;
-                };
+                let cell = self.get_cell(relative_x + x, relative_y + y)?;
 
                 // cell is owned by a player and this is not us -> does not fit
                 if cell.player_id >= 0 && cell.player_id != player_id {
@@ -245,29 +299,26 @@ impl Grid {
                 if (relative_x == 0 || relative_x == w - 1)
                     || (relative_y == 0 || relative_y == h - 1)
                 {
-                    perimeter.insert(cell.clone());
+                    perimeter.push(cell.clone());
                 }
             }
         }
 
-        for cell in perimeter.clone().into_iter() {
-            if cell.player_id <= -1 {
-                return Some(perimeter.into_iter().collect());
-            }
+        if perimeter.iter().any(|cell| cell.player_id < 0) {
+            return Some(perimeter);
         }
-
         None
     }
 
-    pub fn from_sub_grid(cells: Vec<Cell>) -> Self {
-        let mut grid = Grid::default();
+    /// an empty grid of the same size showing only the given cells
+    pub fn get_sub_grid(&self, cells: Vec<Cell>) -> Self {
+        let mut grid = Grid::new(self.width, self.height);
         for mut cell in cells {
-            let x = cell.x;
-            let y = cell.y;
+            let index = grid.get_cell_index(cell.x, cell.y);
             if cell.player_id < 0 {
                 cell.debug_char = 'x';
             }
-            grid.cells[Grid::get_cell_index(x, y)] = cell.clone()
+            grid.cells[index] = cell.clone()
         }
         grid
     }
@@ -307,14 +358,193 @@ impl Grid {
     }
 }
 
-#[derive(Default)]
+/// counts the cells matching a predicate in any rectangle of the grid in O(1)
+struct SummedAreaTable {
+    width: i32,
+    /// sums[(y * width + x)] is the number of matching cells above and left of x,y (excluded)
+    sums: Vec<i32>,
+}
+
+impl SummedAreaTable {
+    pub fn new(grid: &Grid, predicate: impl Fn(&Cell) -> bool) -> Self {
+        let width = grid.width + 1;
+        let mut sums = vec![0; (width * (grid.height + 1)) as usize];
+        // cells are ordered by row, the cells above and left are always computed before
+        for cell in grid.cells.iter() {
+            let index = ((cell.y + 1) * width + cell.x + 1) as usize;
+            sums[index] =
+                i32::from(predicate(cell)) + sums[index - 1] + sums[index - width as usize]
+                    - sums[index - width as usize - 1];
+        }
+        Self { width, sums }
+    }
+
+    fn get(&self, x: i32, y: i32) -> i32 {
+        self.sums[(y * self.width + x) as usize]
+    }
+
+    /// number of matching cells in the rectangle of size w*h, x,y being the top/left corner
+    pub fn count(&self, x: i32, y: i32, w: i32, h: i32) -> i32 {
+        if w <= 0 || h <= 0 {
+            return 0;
+        }
+        self.get(x + w, y + h) - self.get(x, y + h) - self.get(x + w, y) + self.get(x, y)
+    }
+}
+
+#[derive(Debug)]
+struct Rectangle {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+    /// cells we own once closed, per round needed to close it
+    pub score: f64,
+}
+
+/// the better rectangle is the greater one: the best score, then the smallest (h, w, y, x) on a tie
+impl Ord for Rectangle {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score.total_cmp(&other.score).then_with(|| {
+            (other.h, other.w, other.y, other.x).cmp(&(self.h, self.w, self.y, self.x))
+        })
+    }
+}
+
+impl PartialOrd for Rectangle {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Rectangle {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Rectangle {}
+
+/// the state of a round, used to go back in time and to replay what happened since then
+#[derive(Clone, Debug)]
+struct Snapshot {
+    pub round: i32,
+    pub width: i32,
+    pub height: i32,
+    /// player_id owning each cell (indexed like `Grid::cells`), -1 if neutral
+    pub owners: Vec<i32>,
+    /// position of each player (indexed by player id)
+    pub positions: Vec<[i32; 2]>,
+}
+
+impl Snapshot {
+    pub fn get_cell_index(&self, x: i32, y: i32) -> usize {
+        (y * self.width + x) as usize
+    }
+
+    pub fn count_cells(&self, player_id: i32) -> i32 {
+        self.owners.iter().filter(|&&id| id == player_id).count() as i32
+    }
+
+    /// moves the player one cell toward the target, along the x axis first
+    pub fn get_next_position(from: [i32; 2], to: [i32; 2]) -> [i32; 2] {
+        if from[0] != to[0] {
+            return [from[0] + (to[0] - from[0]).signum(), from[1]];
+        }
+        [from[0], from[1] + (to[1] - from[1]).signum()]
+    }
+
+    /// plays a round: every player goes to its next position and takes the cell if it is neutral
+    /// two players entering the same neutral cell at the same round leave it neutral
+    pub fn play_round(&mut self, next_positions: &[[i32; 2]]) {
+        self.round += 1;
+        self.positions = next_positions.to_vec();
+        territory::claim_cells(self.width, &mut self.owners, &self.positions);
+        territory::capture_cells(self.width, self.height, &mut self.owners, &self.positions);
+    }
+
+    /// neutral cells the player captures with the cells it owns (see `territory::get_captured_cells`)
+    pub fn get_captured_cells(&self, player_id: i32) -> Vec<usize> {
+        territory::get_captured_cells(
+            self.width,
+            self.height,
+            &self.owners,
+            &self.positions,
+            player_id,
+        )
+    }
+
+    /// draws the given path for the player (taking only the neutral cells) then applies the captures
+    /// returns the number of cells the player owns at the end, to score any shape of loop
+    pub fn score_path(&self, player_id: i32, path: &[[i32; 2]]) -> i32 {
+        let mut snapshot = self.clone();
+        for position in path {
+            let index = snapshot.get_cell_index(position[0], position[1]);
+            if snapshot.owners[index] < 0 {
+                snapshot.owners[index] = player_id;
+            }
+        }
+        for index in snapshot.get_captured_cells(player_id) {
+            snapshot.owners[index] = player_id;
+        }
+        snapshot.count_cells(player_id)
+    }
+}
+
+/// the tunable values, the constants unless overridden (see `src/params.rs`)
+struct Params {
+    pub back_in_time_min_gain: i32,
+    pub max_safety_checks: usize,
+    pub distance_weight: i32,
+    pub area_exponent: i32,
+}
+
+impl Params {
+    pub fn load() -> Self {
+        Self {
+            back_in_time_min_gain: params::get("back_in_time_min_gain", BACK_IN_TIME_MIN_GAIN),
+            max_safety_checks: params::get("max_safety_checks", MAX_SAFETY_CHECKS),
+            distance_weight: params::get("distance_weight", DISTANCE_WEIGHT),
+            area_exponent: params::get("area_exponent", AREA_EXPONENT),
+        }
+    }
+
+    /// the cells won raised to `area_exponent`, a loss stays negative
+    pub fn weigh_gain(&self, gain: i32) -> f64 {
+        let gain = f64::from(gain);
+        gain.signum() * gain.abs().powf(f64::from(self.area_exponent) / 100.)
+    }
+}
+
 struct Game {
     pub round: i32,
     pub players: Vec<Player>,
     pub grid: Grid,
+    /// one snapshot per round, the oldest first
+    pub history: Vec<Snapshot>,
+    /// the snapshot we expect to get next round after going back in time
+    pub back_in_time_to: Option<Snapshot>,
+    /// cells of the perimeter to take before the others
+    pub priority_cells: Vec<[i32; 2]>,
+    /// the time left this turn, searches stop early with their best result so far
+    pub timer: TurnTimer,
+    pub params: Params,
 }
 
 impl Game {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            round: 0,
+            players: Vec::new(),
+            grid: Grid::new(width, height),
+            history: Vec::new(),
+            back_in_time_to: None,
+            priority_cells: Vec::new(),
+            timer: TurnTimer::unlimited(),
+            params: Params::load(),
+        }
+    }
+
     /**
      * set player input, player with id: 0 is us, others are opponents.
      */
@@ -341,20 +571,417 @@ impl Game {
         self
     }
 
+    pub fn get_snapshot(&self) -> Snapshot {
+        Snapshot {
+            round: self.round,
+            width: self.grid.width,
+            height: self.grid.height,
+            owners: self.grid.cells.iter().map(|cell| cell.player_id).collect(),
+            positions: self.players.iter().map(|p| [p.x, p.y]).collect(),
+        }
+    }
+
+    /// saves the current round in the history
+    /// a cell never goes back to neutral, if it does someone went back in time and the history after this point is wrong
+    pub fn save_snapshot(&mut self) -> &mut Self {
+        let snapshot = self.get_snapshot();
+        if let Some(expected) = self.back_in_time_to.take() {
+            if expected.owners == snapshot.owners && expected.positions == snapshot.positions {
+                info!("back in time to round {}: state restored", expected.round);
+            } else {
+                warn!(
+                    "back in time to round {}: state differs from our snapshot!",
+                    expected.round
+                );
+            }
+        }
+        if let Some(last) = self.history.last() {
+            let rewound = last
+                .owners
+                .iter()
+                .zip(snapshot.owners.iter())
+                .any(|(before, now)| *before >= 0 && *now < 0);
+            if rewound || last.round >= snapshot.round {
+                info!("someone went back in time, clearing history");
+                self.history.clear();
+            }
+        }
+        self.history.push(snapshot);
+        if self.history.len() > MAX_BACK_IN_TIME as usize + 1 {
+            self.history.remove(0);
+        }
+        self
+    }
+
+    /// positions of every player for the `rounds` rounds following the snapshot `rounds_back` rounds ago
+    /// this is what they did last time, then they keep going in the same direction
+    pub fn get_trajectory(&self, rounds_back: usize, rounds: usize) -> Vec<Vec<[i32; 2]>> {
+        let mut trajectory = self.history[self.history.len() - rounds_back..]
+            .iter()
+            .map(|s| s.positions.clone())
+            .collect::<Vec<_>>();
+        let mut previous = self.history[self.history.len() - 1 - rounds_back]
+            .positions
+            .clone();
+        if let Some(before_last) = trajectory.iter().rev().nth(1) {
+            previous = before_last.clone();
+        }
+        while trajectory.len() < rounds {
+            let last = trajectory.last().unwrap();
+            let next = last
+                .iter()
+                .zip(previous.iter())
+                .map(|(position, before)| {
+                    [
+                        (2 * position[0] - before[0]).clamp(0, self.grid.width - 1),
+                        (2 * position[1] - before[1]).clamp(0, self.grid.height - 1),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            previous = last.clone();
+            trajectory.push(next);
+        }
+        trajectory
+    }
+
+    /// perimeter cells opponents are walking on, in the order they do
+    pub fn get_threatened_cells(trajectory: &[Vec<[i32; 2]>], perimeter: &[Cell]) -> Vec<[i32; 2]> {
+        let mut cells = Vec::new();
+        for positions in trajectory {
+            for position in positions.iter().skip(1) {
+                if !cells.contains(position)
+                    && perimeter
+                        .iter()
+                        .any(|cell| cell.x == position[0] && cell.y == position[1])
+                {
+                    cells.push(*position);
+                }
+            }
+        }
+        cells
+    }
+
+    /// replays the game from the snapshot `rounds_back` rounds ago while opponents follow the given trajectory
+    /// we take the threatened cells first, then follow the perimeter going to the closest cell not owned yet
+    /// returns the number of cells we own once the perimeter is closed (or once an opponent broke it)
+    pub fn simulate_back_in_time(
+        &self,
+        rounds_back: usize,
+        perimeter: &[Cell],
+        trajectory: &[Vec<[i32; 2]>],
+        threatened_cells: &[[i32; 2]],
+    ) -> i32 {
+        let mut snapshot = self.history[self.history.len() - 1 - rounds_back].clone();
+
+        let perimeter_indexes = perimeter
+            .iter()
+            .map(|cell| snapshot.get_cell_index(cell.x, cell.y))
+            .collect::<Vec<_>>();
+        for positions in trajectory {
+            let mut next_positions = positions.clone();
+            let position = snapshot.positions[0];
+            let is_not_ours = |x: i32, y: i32| snapshot.owners[snapshot.get_cell_index(x, y)] != 0;
+            let Some(target) = threatened_cells
+                .iter()
+                .find(|cell| is_not_ours(cell[0], cell[1]))
+                .copied()
+                .or_else(|| {
+                    perimeter
+                        .iter()
+                        .filter(|cell| is_not_ours(cell.x, cell.y))
+                        .min_by_key(|cell| {
+                            (cell.x - position[0]).pow(2) + (cell.y - position[1]).pow(2)
+                        })
+                        .map(|cell| [cell.x, cell.y])
+                })
+            else {
+                break;
+            };
+            next_positions[0] = Snapshot::get_next_position(position, target);
+            snapshot.play_round(&next_positions);
+
+            if perimeter_indexes
+                .iter()
+                .any(|index| snapshot.owners[*index] > 0)
+            {
+                return snapshot.count_cells(0);
+            }
+        }
+
+        snapshot.count_cells(0)
+    }
+
+    /// when an opponent cut through our unfinished perimeter, check if going back in time gets us more cells than continuing
+    /// returns the number of rounds to go back and the perimeter cells to take first this time
+    pub fn get_back_in_time(&self, perimeter: &[Cell]) -> Option<(i32, Vec<[i32; 2]>)> {
+        if self.players.first()?.back_in_time_left <= 0 {
+            return None;
+        }
+        let is_broken = perimeter.iter().any(|cell| {
+            self.grid
+                .get_cell(cell.x, cell.y)
+                .is_some_and(|cell| cell.player_id > 0)
+        });
+        if !is_broken {
+            return None;
+        }
+
+        let current_count = self.get_snapshot().count_cells(0);
+        let mut best: Option<(i32, i32, Vec<[i32; 2]>)> = None;
+        for rounds_back in 1..self.history.len().min(MAX_BACK_IN_TIME as usize + 1) {
+            if self.timer.should_stop(TIME_MARGIN) {
+                warn!("back in time: out of time after {} rounds", rounds_back - 1);
+                break;
+            }
+            let trajectory = self.get_trajectory(rounds_back, rounds_back + perimeter.len() * 2);
+            let threatened_cells = Game::get_threatened_cells(&trajectory, perimeter);
+            // going back costs the rounds we could have spent painting cells
+            let gain =
+                self.simulate_back_in_time(rounds_back, perimeter, &trajectory, &threatened_cells)
+                    - current_count
+                    - rounds_back as i32;
+            debug!("back in time {}: gain {}", rounds_back, gain);
+            match best {
+                Some((best_gain, _, _)) if best_gain >= gain => {}
+                _ => best = Some((gain, rounds_back as i32, threatened_cells)),
+            }
+        }
+
+        match best {
+            Some((gain, rounds_back, threatened_cells))
+                if gain >= self.params.back_in_time_min_gain =>
+            {
+                Some((rounds_back, threatened_cells))
+            }
+            _ => None,
+        }
+    }
+
     pub fn compute_distances(&mut self) -> &mut Self {
         for player in self.players.iter() {
             self.grid
                 .compute_all_distances_player(player.id, player.x, player.y);
+            self.grid
+                .compute_all_steps_player(player.id, player.x, player.y);
         }
         self
     }
+
+    /// the round (from now) at which we take each perimeter cell we do not own yet
+    /// we always go to the closest cell of the perimeter, like we do while playing
+    pub fn get_closing_rounds(&self, perimeter: &[Cell]) -> Vec<([i32; 2], i32)> {
+        let Some(player) = self.players.first() else {
+            return Vec::new();
+        };
+        let mut left = perimeter
+            .iter()
+            .filter(|cell| {
+                self.grid
+                    .get_cell(cell.x, cell.y)
+                    .is_some_and(|cell| cell.player_id != player.id)
+            })
+            .map(|cell| [cell.x, cell.y])
+            .collect::<Vec<_>>();
+        let mut closing_rounds = Vec::with_capacity(left.len());
+        let mut position = [player.x, player.y];
+        let mut round = 0;
+        while let Some(target) = left
+            .iter()
+            .min_by_key(|cell| (cell[0] - position[0]).pow(2) + (cell[1] - position[1]).pow(2))
+            .copied()
+        {
+            position = Snapshot::get_next_position(position, target);
+            round += 1;
+            if let Some(index) = left.iter().position(|cell| *cell == position) {
+                closing_rounds.push((left.swap_remove(index), round));
+            }
+        }
+        closing_rounds
+    }
+
+    /// a perimeter is safe if no opponent can reach a perimeter cell before (or at the same round as) we take it
+    pub fn is_perimeter_safe(&self, perimeter: &[Cell]) -> bool {
+        for (position, round) in self.get_closing_rounds(perimeter) {
+            let Some(cell) = self.grid.get_cell(position[0], position[1]) else {
+                continue;
+            };
+            for player in self.players.iter().skip(1) {
+                if cell
+                    .steps
+                    .get(&player.id)
+                    .is_some_and(|steps| *steps <= round)
+                {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// cells we win per round spent to close the perimeter, `None` if an opponent can break it
+    pub fn score_perimeter(&self, perimeter: &[Cell]) -> Option<f64> {
+        if !self.is_perimeter_safe(perimeter) {
+            return None;
+        }
+        let snapshot = self.get_snapshot();
+        let path = perimeter
+            .iter()
+            .map(|cell| [cell.x, cell.y])
+            .collect::<Vec<_>>();
+        let gain = snapshot.score_path(0, &path) - snapshot.count_cells(0);
+        let rounds = self
+            .get_closing_rounds(perimeter)
+            .iter()
+            .map(|(_, round)| *round)
+            .max()
+            .unwrap_or(0);
+        Some(self.params.weigh_gain(gain) / f64::from(rounds.max(1)))
+    }
+
+    /// the `count` best rectangles without cells of other players and with a perimeter not closed yet, the best first
+    /// - the gain is every neutral cell inside the rectangle, we get them all once closed
+    /// - the rounds are the distance to the perimeter (weighted by `distance_weight`) plus one round per neutral
+    ///   perimeter cell
+    ///
+    /// a 35x20 board has about 130k rectangles, only the best ones are kept in a heap (its top is the worst kept)
+    pub fn get_rectangles(&self, count: usize) -> Vec<Rectangle> {
+        let Some(player) = self.players.first() else {
+            return Vec::new();
+        };
+        let foreign = SummedAreaTable::new(&self.grid, |cell| {
+            cell.player_id >= 0 && cell.player_id != player.id
+        });
+        let neutral = SummedAreaTable::new(&self.grid, |cell| cell.player_id < 0);
+        // the weighted gain of every number of cells, `powf` is too slow for every rectangle
+        let weighted_gains = (0..=self.grid.width * self.grid.height)
+            .map(|gain| self.params.weigh_gain(gain))
+            .collect::<Vec<_>>();
+        let distance_weight = f64::from(self.params.distance_weight) / 100.;
+
+        let mut rectangles = BinaryHeap::new();
+        for h in 2..=self.grid.height {
+            for w in 2..=self.grid.width {
+                for y in 0..=self.grid.height - h {
+                    for x in 0..=self.grid.width - w {
+                        if foreign.count(x, y, w, h) > 0 {
+                            continue;
+                        }
+                        let gain = neutral.count(x, y, w, h);
+                        let perimeter_left = gain - neutral.count(x + 1, y + 1, w - 2, h - 2);
+                        if perimeter_left == 0 {
+                            continue;
+                        }
+                        let (max_x, max_y) = (x + w - 1, y + h - 1);
+                        let is_inside =
+                            (x..=max_x).contains(&player.x) && (y..=max_y).contains(&player.y);
+                        let distance = if is_inside {
+                            (player.x - x)
+                                .min(max_x - player.x)
+                                .min(player.y - y)
+                                .min(max_y - player.y)
+                        } else {
+                            (x - player.x).max(player.x - max_x).max(0)
+                                + (y - player.y).max(player.y - max_y).max(0)
+                        };
+                        let rectangle = Rectangle {
+                            x,
+                            y,
+                            w,
+                            h,
+                            score: weighted_gains[gain as usize]
+                                / (f64::from(distance) * distance_weight
+                                    + f64::from(perimeter_left)),
+                        };
+                        match rectangles.peek() {
+                            Some(Reverse(worst)) if rectangles.len() >= count => {
+                                if rectangle > *worst {
+                                    rectangles.pop();
+                                    rectangles.push(Reverse(rectangle));
+                                }
+                            }
+                            _ => rectangles.push(Reverse(rectangle)),
+                        }
+                    }
+                }
+            }
+        }
+        rectangles
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(rectangle)| rectangle)
+            .collect()
+    }
+
+    /// the safe perimeter with the best `score_perimeter` among the best rectangles of `get_rectangles`
+    ///
+    /// when none of them is safe we still draw the best rectangle: idling wins nothing, an opponent may not go for
+    /// it, and if one cuts it we can still go back in time (see `get_back_in_time`)
+    pub fn get_best_perimeter(&self) -> Option<Vec<Cell>> {
+        let player = self.players.first()?;
+        // the best rectangle is kept even without safety checks, we draw it when none is safe
+        let rectangles = self.get_rectangles(self.params.max_safety_checks.max(1));
+        let mut best: Option<(f64, &Rectangle, Vec<Cell>)> = None;
+        for (index, rectangle) in rectangles
+            .iter()
+            .take(self.params.max_safety_checks)
+            .enumerate()
+        {
+            if self.timer.should_stop(TIME_MARGIN) {
+                warn!("perimeter: out of time after {} safety checks", index);
+                break;
+            }
+            let Some(perimeter) = self.grid.get_fitting_perimeter(
+                rectangle.w,
+                rectangle.h,
+                rectangle.x,
+                rectangle.y,
+                player.id,
+            ) else {
+                continue;
+            };
+            let Some(score) = self.score_perimeter(&perimeter) else {
+                continue;
+            };
+            match best {
+                Some((best_score, _, _)) if best_score >= score => {}
+                _ => best = Some((score, rectangle, perimeter)),
+            }
+        }
+        if let Some((score, rectangle, perimeter)) = best {
+            info!("safe perimeter {:?} score={:.2}", rectangle, score);
+            return Some(perimeter);
+        }
+
+        let rectangle = rectangles.first()?;
+        info!("no safe perimeter, drawing {:?} anyway", rectangle);
+        self.grid.get_fitting_perimeter(
+            rectangle.w,
+            rectangle.h,
+            rectangle.x,
+            rectangle.y,
+            player.id,
+        )
+    }
 }
 
 fn main() {
+    // the board size is not part of the inputs, a local referee can run smaller boards
+    let mut width = DEFAULT_WIDTH;
+    let mut height = DEFAULT_HEIGHT;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--size" {
+            let size = args.next().expect("--size needs a value (WxH)");
+            let (w, h) = size.split_once('x').expect("--size is WxH");
+            width = parse_input!(w, i32);
+            height = parse_input!(h, i32);
+        }
+    }
+
     let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
+    transcript::read_line(&mut input_line);
     let opponent_count = parse_input!(input_line, usize);
-    let mut game = Game::default();
+    let mut game = Game::new(width, height);
 
     let mut best_perimeter = Option::<Vec<Cell>>::None;
     let mut best_perimeter_x = 0;
@@ -368,93 +995,77 @@ fn main() {
     loop {
         // parse inputs
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         game.round = parse_input!(input_line, i32);
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         game.set_player_inputs(0, input_line);
         for i in 0..opponent_count {
             let mut input_line = String::new();
-            io::stdin().read_line(&mut input_line).unwrap();
+            transcript::read_line(&mut input_line);
             game.set_player_inputs(i + 1, input_line);
         }
-        for i in 0..HEIGHT as usize {
+        for i in 0..game.grid.height as usize {
             let mut input_line = String::new();
-            io::stdin().read_line(&mut input_line).unwrap();
+            transcript::read_line(&mut input_line);
             game.set_grid_line(i, input_line);
         }
+        game.timer = TurnTimer::for_turn(timer::BACK_TO_THE_CODE, game.round);
 
         // some computations
+        game.save_snapshot();
         game.compute_distances();
 
-        for i in 0..game.players.len() {
-            eprintln!("player {} distances:", i);
-            eprintln!("{}", game.grid.debug_distances(i as i32));
-        }
-        let get_best_perimeter_for_w = |w: i32, h: i32| {
-            let player = game.players.get(0).unwrap();
-            let cells = game
-                .grid
-                .get_fitting_perimeter(w, h, player.x - w + 1, player.y - h + 1, player.id)
-                .or_else(|| {
-                    game.grid
-                        .get_fitting_perimeter(w, h, player.x, player.y - h + 1, player.id)
-                })
-                .or_else(|| {
-                    game.grid
-                        .get_fitting_perimeter(w, h, player.x - w + 1, player.y, player.id)
-                })
-                .or_else(|| {
-                    game.grid
-                        .get_fitting_perimeter(w, h, player.x, player.y, player.id)
-                });
-            if cells.is_none() {
-                for x in 0..WIDTH {
-                    for y in 0..HEIGHT {
-                        if let Some(cells) = game.grid.get_fitting_perimeter(w, h, x, y, player.id)
-                        {
-                            return Option::Some(cells);
-                        }
-                    }
-                }
-            }
-            cells
-        };
-
-        let get_best_perimeter = || {
-            let max_w: i32 = WIDTH - 1;
-            let max_h: i32 = HEIGHT - 1;
-
-            for w in (2..max_w).rev() {
-                for h in (w..max_h).rev() {
-                    let perimeter = get_best_perimeter_for_w(w, h);
-                    if perimeter.is_some() {
-                        return perimeter;
-                    }
-                }
+        // an opponent broke our perimeter, maybe going back in time is worth it
+        if let Some(cells) = &best_perimeter {
+            if let Some((rounds_back, threatened_cells)) = game.get_back_in_time(cells) {
+                write_answer!("BACK {}", rounds_back);
+                game.back_in_time_to = game
+                    .history
+                    .get(game.history.len() - 1 - rounds_back as usize)
+                    .cloned();
+                // same perimeter, but this time we close it where opponents will cut first
+                game.priority_cells = threatened_cells;
+                targeted_cell = None;
+                continue;
             }
+        }
 
-            None
-        };
+        for i in 0..game.players.len() {
+            trace!(
+                target: "distances",
+                "player {} distances:\n{}",
+                i,
+                game.grid.debug_distances(i as i32)
+            );
+        }
 
         // find the best perimeter we keep for multiple loop
         let mut set_best_perimeter = || {
             if best_perimeter.is_none() {
-                if let Some(mut cells) = get_best_perimeter() {
+                if let Some(mut cells) = game.get_best_perimeter() {
                     cells.sort_by(|a, b| b.y.cmp(&a.y.clone()));
                     cells.sort_by(|a, b| b.x.cmp(&a.x.clone()));
-                    let sub_grid = Grid::from_sub_grid(cells.clone());
                     if let Some(min_xy) = Grid::get_min_xy(cells.clone()) {
                         best_perimeter_x = min_xy[0];
                         best_perimeter_y = min_xy[1];
                         if let Some(max_xy) = Grid::get_max_xy(cells.clone()) {
-                            best_perimeter_w = max_xy[0] - min_xy[0];
-                            best_perimeter_h = max_xy[1] - min_xy[1];
+                            best_perimeter_w = max_xy[0] - min_xy[0] + 1;
+                            best_perimeter_h = max_xy[1] - min_xy[1] + 1;
                         }
                     }
+                    let path = cells
+                        .iter()
+                        .map(|cell| [cell.x, cell.y])
+                        .collect::<Vec<_>>();
+                    info!(
+                        "perimeter: we would own {} cells once closed",
+                        game.get_snapshot().score_path(0, &path)
+                    );
+                    trace!(target: "grid", "{}", game.grid.get_sub_grid(cells.clone()));
+                    // the cells threatened on the previous perimeter, not on this one
+                    game.priority_cells.clear();
                     best_perimeter = Some(cells);
-
-                    eprintln!("{}", sub_grid);
                 }
             }
         };
@@ -467,6 +1078,14 @@ fn main() {
 
             // go to the closest cells of the player that is not already drawn
             let mut set_targeted_cell = || {
+                if let Some(cell) = game.priority_cells.iter().find_map(|position| {
+                    game.grid
+                        .get_cell(position[0], position[1])
+                        .filter(|cell| cell.player_id < 0)
+                }) {
+                    targeted_cell = Some(cell.clone());
+                    return;
+                }
                 let mut cells = cells.clone();
                 cells.sort_by(|a, b| {
                     game.grid
@@ -497,17 +1116,16 @@ fn main() {
                 targeted_cell = Some(cell);
             };
 
-            if cloned_targeted_cell.is_none() {
-                set_targeted_cell();
-            } else {
-                let cell = cloned_targeted_cell.unwrap();
-                eprintln!(
+            if let Some(cell) = cloned_targeted_cell {
+                debug!(
                     "p.x={};p.y={} - t.x={};t.y={}",
                     game.players[0].x, game.players[0].y, cell.x, cell.y
                 );
                 if game.players[0].is_at(&cell) {
                     set_targeted_cell();
                 }
+            } else {
+                set_targeted_cell();
             }
 
             if let Some(cell) = &targeted_cell {
@@ -518,9 +1136,9 @@ fn main() {
                     }
                     Some(cell) => {
                         printed = true;
-                        println!("{} {}", cell.x, cell.y);
+                        write_answer!("{} {}", cell.x, cell.y);
                         // eprintln!("{}", game.grid);
-                        eprintln!(
+                        debug!(
                             "w={};h={};x={};y={}",
                             best_perimeter_w, best_perimeter_h, best_perimeter_x, best_perimeter_y
                         );
@@ -545,7 +1163,186 @@ fn main() {
         }
 
         if !printed {
-            println!("0 0 - FUCK");
+            write_answer!("0 0 - FUCK");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codinggame_backtocode_rust::random::Random;
+    use std::time::Instant;
+
+    /// gives the board to the bot like the referee does, then saves the round like the game loop
+    fn feed(game: &mut Game, board: &Snapshot, round: i32) {
+        game.round = round;
+        for (player_id, position) in board.positions.iter().enumerate() {
+            game.set_player_inputs(player_id, format!("{} {} 1", position[0], position[1]));
+        }
+        for y in 0..board.height {
+            let row = (0..board.width)
+                .map(|x| match board.owners[board.get_cell_index(x, y)] {
+                    owner if owner < 0 => '.',
+                    owner => char::from_digit(owner as u32, 10).unwrap(),
+                })
+                .collect::<String>();
+            game.set_grid_line(y as usize, row);
         }
+        game.save_snapshot();
+    }
+
+    /// a 5x4 board, we own a ring around the cell 1,1
+    fn get_small_game() -> Game {
+        let mut game = Game::new(5, 4);
+        game.set_player_inputs(0, "0 0 1".to_string());
+        game.set_player_inputs(1, "4 3 1".to_string());
+        for (y, row) in ["000..", "0.0..", "000..", "....1"].iter().enumerate() {
+            game.set_grid_line(y, row.to_string());
+        }
+        game
+    }
+
+    #[test]
+    fn small_board_indexes_and_distances() {
+        let game = get_small_game();
+        assert_eq!(game.grid.cells.len(), 20);
+        assert_eq!(game.grid.get_cell_index(0, 1), 5);
+        assert_eq!(game.grid.get_cell_index(4, 3), 19);
+        assert!(game.grid.get_cell(5, 0).is_none());
+        assert!(game.grid.get_cell(0, 4).is_none());
+        assert_eq!(game.grid.get_max_distance(), 41.);
+    }
+
+    #[test]
+    fn small_board_capture() {
+        let mut game = get_small_game();
+        assert_eq!(game.get_snapshot().get_captured_cells(0), vec![6]);
+        assert!(game.get_snapshot().get_captured_cells(1).is_empty());
+        // an opponent inside the ring keeps it neutral
+        game.set_player_inputs(1, "1 1 1".to_string());
+        assert!(game.get_snapshot().get_captured_cells(0).is_empty());
+    }
+
+    #[test]
+    fn small_board_best_rectangles() {
+        let mut game = get_small_game();
+        game.compute_distances();
+        let all = game.get_rectangles(usize::MAX);
+        assert!(all.windows(2).all(|pair| pair[0] >= pair[1]));
+        // the heap keeps the same best rectangles as the whole list, in the same order
+        let best = game.get_rectangles(3);
+        assert_eq!(best, all[..3]);
+    }
+
+    #[test]
+    fn small_board_perimeter() {
+        let mut game = get_small_game();
+        game.compute_distances();
+        let perimeter = game.get_best_perimeter().unwrap();
+        assert!(perimeter
+            .iter()
+            .all(|cell| game.grid.is_in_bounds(cell.x, cell.y) && cell.player_id != 1));
+        assert!(perimeter.iter().any(|cell| cell.player_id < 0));
+    }
+
+    #[test]
+    fn small_board_display() {
+        let game = get_small_game();
+        assert_eq!(game.grid.to_string(), "000..\n0.0..\n000..\n....1\n");
+    }
+
+    #[test]
+    fn back_in_time_gives_back_the_snapshot_of_that_round() {
+        let mut game = Game::new(6, 5);
+        let mut board = Snapshot {
+            round: 0,
+            width: 6,
+            height: 5,
+            owners: vec![-1; 30],
+            positions: vec![[0, 0], [5, 4]],
+        };
+        // we draw the top row while the opponent goes up the right column
+        let targets = [[5, 0], [5, 1]];
+        board.play_round(&board.positions.clone());
+        feed(&mut game, &board, 1);
+        for round in 2..=8 {
+            let next_positions = board
+                .positions
+                .iter()
+                .zip(targets)
+                .map(|(position, target)| Snapshot::get_next_position(*position, target))
+                .collect::<Vec<_>>();
+            board.play_round(&next_positions);
+            feed(&mut game, &board, round);
+        }
+
+        // what the game loop keeps when answering `BACK 4`
+        let rounds_back = 4;
+        let expected = game.history[game.history.len() - 1 - rounds_back].clone();
+        assert_eq!(expected.round, 4);
+        assert_ne!(expected.owners, game.get_snapshot().owners);
+        game.back_in_time_to = Some(expected.clone());
+
+        // the referee sends the board of round 4 back, the round counter keeps going
+        feed(&mut game, &expected, 9);
+        let snapshot = game.get_snapshot();
+        assert_eq!(snapshot.owners, expected.owners);
+        assert_eq!(snapshot.positions, expected.positions);
+        assert_eq!(game.players[0].x, 3);
+        assert!(game
+            .grid
+            .get_cell(4, 0)
+            .is_some_and(|cell| cell.player_id < 0));
+        // the rounds after round 4 did not happen, the history is started again
+        assert_eq!(game.history.len(), 1);
+        assert!(game.back_in_time_to.is_none());
+    }
+
+    /// times the turn computations on a board half taken by 4 players, the per-turn cost is printed with
+    /// `cargo test --release --bin back-to-the-code -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_turns() {
+        let (width, height, turns) = (DEFAULT_WIDTH, DEFAULT_HEIGHT, 350);
+        let mut game = Game::new(width, height);
+        let mut random = Random::new(42);
+        for player_id in 0..4 {
+            let (x, y) = (random.range(0, width), random.range(0, height));
+            game.set_player_inputs(player_id, format!("{} {} 1", x, y));
+        }
+
+        let mut durations = Vec::with_capacity(turns);
+        for round in 1..=turns {
+            game.round = round as i32;
+            for player_id in 0..game.players.len() {
+                let (x, y) = (random.range(0, width), random.range(0, height));
+                game.set_player_inputs(player_id, format!("{} {} 1", x, y));
+                if let Some(cell) = game.grid.get_cell_mut(x, y) {
+                    if cell.player_id < 0 && round < width as usize * height as usize / 8 {
+                        cell.player_id = player_id as i32;
+                    }
+                }
+            }
+
+            let start = Instant::now();
+            game.save_snapshot();
+            game.compute_distances();
+            let perimeter = game.get_best_perimeter();
+            durations.push(start.elapsed());
+            assert!(perimeter.is_some() || round > 1);
+        }
+
+        let total = durations.iter().sum::<Duration>();
+        let max = *durations.iter().max().unwrap();
+        println!(
+            "{} turns on {}x{}: {:.3}ms per turn, {:.3}ms max",
+            turns,
+            width,
+            height,
+            total.as_secs_f64() * 1000. / turns as f64,
+            max.as_secs_f64() * 1000.
+        );
+        assert!(max < timer::BACK_TO_THE_CODE.get(2));
     }
 }
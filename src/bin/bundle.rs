@@ -0,0 +1,135 @@
+// bundles a bot and the library into a single source file to paste into CodinGame, which compiles one file
+// without the crates around it
+//
+// usage: bundle <bot> [output]
+//
+// the bot is a name from `src/bin` (`code-a-la-mode`) or a path, the source goes on stdout without an output file
+//
+// the modules of `src/lib.rs` are appended to the bot inside `mod codinggame_backtocode_rust`, so its imports are
+// unchanged:
+// - `crate::` and `$crate::` paths go through that module
+// - `#[macro_export]` macros are exported by path from their module instead of the crate root, so the bot imports
+//   them from the library like before
+// - the `submission` feature is on, the debug lines under `info` are stripped (see `src/log.rs`)
+// - the test modules are left out, CodinGame takes up to 100k characters
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const LIB_NAME: &str = "codinggame_backtocode_rust";
+
+/// the source without its `#[cfg(test)]` module
+fn strip_tests(source: &str) -> String {
+    let mut lines = Vec::new();
+    let mut source_lines = source.lines();
+    while let Some(line) = source_lines.next() {
+        if line == "#[cfg(test)]" {
+            // the test module goes on until its closing brace at the start of a line
+            for line in source_lines.by_ref() {
+                if line == "}" {
+                    break;
+                }
+            }
+        } else {
+            lines.push(line);
+        }
+    }
+    lines.join("\n")
+}
+
+/// the module source with its paths and macros working inside the bundle, and its exported macros
+fn bundle_module(source: &str) -> (String, Vec<String>) {
+    let source = strip_tests(source).replace("crate::", &format!("crate::{}::", LIB_NAME));
+    let mut lines = Vec::new();
+    let mut macros = Vec::new();
+    for line in source.lines() {
+        if line == "#[macro_export]" {
+            continue;
+        }
+        match line
+            .strip_prefix("macro_rules! ")
+            .and_then(|rest| rest.strip_suffix(" {"))
+        {
+            // renamed, `use warn;` would be ambiguous with the `#[warn]` attribute
+            Some(name) => {
+                lines.push(format!("macro_rules! bundled_{} {{", name));
+                macros.push(name.to_string());
+            }
+            None => lines.push(line.to_string()),
+        }
+    }
+    let mut bundled = lines.join("\n");
+    for name in &macros {
+        bundled.push_str(&format!("\npub(crate) use bundled_{} as {};", name, name));
+    }
+    (bundled, macros)
+}
+
+/// the modules declared in `src/lib.rs`
+fn get_modules(root: &Path) -> Vec<String> {
+    let lib = fs::read_to_string(root.join("src/lib.rs")).expect("src/lib.rs is read");
+    lib.lines()
+        .filter_map(|line| line.strip_prefix("pub mod ")?.strip_suffix(';'))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+fn bundle(root: &Path, bot: &str) -> String {
+    let mut bundled = strip_tests(bot).trim_end().to_string();
+    let mut modules = Vec::new();
+    let mut macros = Vec::new();
+    for name in get_modules(root) {
+        let path = root.join("src").join(format!("{}.rs", name));
+        let source = fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("can not read {}: {}", path.display(), e));
+        let (source, module_macros) = bundle_module(&source);
+        modules.push(format!("pub mod {} {{\n{}\n}}", name, source));
+        macros.extend(
+            module_macros
+                .into_iter()
+                .map(|macro_name| (name.clone(), macro_name)),
+        );
+    }
+
+    bundled.push_str(&format!(
+        "\n\n// src/lib.rs, bundled by `cargo run --bin bundle`\n\
+        #[allow(dead_code, unused_imports, unused_macros)]\n\
+        mod {} {{\n",
+        LIB_NAME
+    ));
+    for (module, name) in macros {
+        bundled.push_str(&format!("pub(crate) use self::{}::{};\n", module, name));
+    }
+    bundled.push('\n');
+    bundled.push_str(&modules.join("\n\n"));
+    bundled.push_str("\n}\n");
+    bundled.replace("feature = \"submission\"", "all()")
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (bot, output) = match &args[..] {
+        [bot] => (bot, None),
+        [bot, output] => (bot, Some(output)),
+        _ => {
+            eprintln!("usage: bundle <bot> [output]");
+            std::process::exit(2);
+        }
+    };
+
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let mut path = PathBuf::from(bot);
+    if !path.exists() {
+        path = root.join("src/bin").join(format!("{}.rs", bot));
+    }
+    let source = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("can not read {}: {}", path.display(), e));
+    let bundled = bundle(&root, &source);
+    match output {
+        Some(output) => {
+            fs::write(output, bundled).unwrap_or_else(|e| panic!("can not write {}: {}", output, e))
+        }
+        None => print!("{}", bundled),
+    }
+}
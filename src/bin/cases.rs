@@ -0,0 +1,604 @@
+// runs the puzzle bots against the cases of `tests/cases` and reports pass/fail per case, `cargo test` runs them all
+//
+// usage: cases [--verbose] [--bots DIR] [--cases DIR] [puzzle...]
+//
+// a case is a text file `tests/cases/<puzzle>/<name>.txt`, the puzzle is the name of the bot binary, the bots are
+// looked for next to this binary (`target/debug` under `cargo test`)
+//
+// the lines before the first section describe the case, then:
+// - `[input]` the initialization lines the bot reads, as given by CodinGame
+// - `[expected]` the answer of a puzzle without turns, for winamax-battle it must be the end of the game the runner
+//   plays itself
+// - `[referee]` what the bot does not see but the referee needs, for the puzzles played in turns:
+//   - shadows-of-the-knight-episode-1: `bomb X Y`
+//   - death-first-search-episode-1 / 2: `agent N` the node the agent starts on
+//   - dont-panic-episode-1: `generator X` the position of the generator on the first floor, clones leave it going
+//     right, a clone coming out of the generator or an elevator on a blocked clone turns around
+//   - the-labyrinth: the rows of the whole maze
+//
+// the referees follow the puzzle statements, the agent of Death First Search always takes a shortest path to the
+// closest gateway (episode 2 prefers the node linked to more gateways on a tie)
+//
+// the cases are regression cases written for this runner, not the validators of CodinGame
+
+use codinggame_backtocode_rust::process::BotProcess;
+use std::collections::{HashSet, VecDeque};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// the Labyrinth: moves before the jetpack runs out of fuel
+const LABYRINTH_FUEL: i32 = 1200;
+/// the Labyrinth: Kirk scans the cells at this distance (a square)
+const LABYRINTH_SCAN: i32 = 2;
+/// Don't Panic: turns between two clones leaving the generator
+const CLONE_INTERVAL: i32 = 3;
+/// Winamax: a game still going after this number of rounds never ends
+const WINAMAX_MAX_ROUNDS: i32 = 100_000;
+
+#[derive(Default)]
+struct Case {
+    input: Vec<String>,
+    expected: Vec<String>,
+    referee: Vec<String>,
+}
+
+impl Case {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut case = Case::default();
+        let mut section: Option<&mut Vec<String>> = None;
+        for line in content.lines() {
+            match line.trim() {
+                "[input]" => section = Some(&mut case.input),
+                "[expected]" => section = Some(&mut case.expected),
+                "[referee]" => section = Some(&mut case.referee),
+                _ => {
+                    if let Some(lines) = section.as_mut() {
+                        lines.push(line.trim_end().to_string());
+                    }
+                }
+            }
+        }
+        for lines in [&mut case.input, &mut case.expected, &mut case.referee] {
+            while lines.last().is_some_and(|line| line.is_empty()) {
+                lines.pop();
+            }
+        }
+        if case.input.is_empty() {
+            return Err("no [input] section".to_string());
+        }
+        Ok(case)
+    }
+
+    /// the values of the referee line starting with the key
+    pub fn get_referee_values(&self, key: &str) -> Result<Vec<i32>, String> {
+        let line = self
+            .referee
+            .iter()
+            .find_map(|line| line.strip_prefix(key))
+            .ok_or_else(|| format!("no `{}` in [referee]", key))?;
+        parse_numbers(line)
+    }
+}
+
+fn parse_numbers(line: &str) -> Result<Vec<i32>, String> {
+    line.split_whitespace()
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("not a number: {}", value))
+        })
+        .collect()
+}
+
+/// the values of an input line
+fn get_input_values(case: &Case, index: usize) -> Result<Vec<i32>, String> {
+    parse_numbers(
+        case.input
+            .get(index)
+            .ok_or_else(|| format!("missing input line {}", index + 1))?,
+    )
+}
+
+/// the answer of the bot for this turn
+fn receive(bot: &mut BotProcess, turn: i32) -> Result<String, String> {
+    bot.receive()
+        .ok_or_else(|| format!("turn {}: the bot died", turn))
+}
+
+/// a puzzle without turns: the whole input, then one answer
+fn run_output(bot: &mut BotProcess, case: &Case) -> Result<String, String> {
+    bot.send(&case.input);
+    let mut answers = Vec::new();
+    for turn in 1..=case.expected.len() {
+        answers.push(receive(bot, turn as i32)?);
+    }
+    if answers != case.expected {
+        return Err(format!(
+            "expected `{}`, got `{}`",
+            case.expected.join(" / "),
+            answers.join(" / ")
+        ));
+    }
+    Ok(format!("`{}`", answers.join(" / ")))
+}
+
+/// Winamax: the value of a card (`10H`, `AD`), its suit left aside
+fn get_card_value(card: &str) -> Result<i32, String> {
+    let value = card.get(..card.len().saturating_sub(1)).unwrap_or_default();
+    match value {
+        "J" => Ok(11),
+        "Q" => Ok(12),
+        "K" => Ok(13),
+        "A" => Ok(14),
+        _ => value
+            .parse()
+            .ok()
+            .filter(|value| (2..=10).contains(value))
+            .ok_or_else(|| format!("not a card: {}", card)),
+    }
+}
+
+/// Winamax: plays the game of the case like the puzzle statement does, the answer the bot must give
+///
+/// a round takes one card of each deck, the higher one wins both, the cards of player 1 first, under its deck, equal
+/// cards start a battle: 3 more cards each are put down then the next ones are compared, a player without the cards
+/// needed is a pat
+fn play_winamax_battle(case: &Case) -> Result<String, String> {
+    let mut decks = [VecDeque::new(), VecDeque::new()];
+    let mut line = 0;
+    for deck in decks.iter_mut() {
+        let count = get_input_values(case, line)?[0] as usize;
+        for index in 1..=count {
+            let card = case
+                .input
+                .get(line + index)
+                .ok_or_else(|| format!("missing input line {}", line + index + 1))?;
+            deck.push_back(get_card_value(card.trim())?);
+        }
+        line += count + 1;
+    }
+
+    let mut rounds = 0;
+    while decks.iter().all(|deck| !deck.is_empty()) {
+        rounds += 1;
+        if rounds > WINAMAX_MAX_ROUNDS {
+            return Err(format!(
+                "the game lasts more than {} rounds",
+                WINAMAX_MAX_ROUNDS
+            ));
+        }
+        let mut piles = [Vec::new(), Vec::new()];
+        loop {
+            for (deck, pile) in decks.iter_mut().zip(piles.iter_mut()) {
+                let Some(card) = deck.pop_front() else {
+                    return Ok("PAT".to_string());
+                };
+                pile.push(card);
+            }
+            let (card_1, card_2) = (piles[0].last(), piles[1].last());
+            if card_1 != card_2 {
+                let winner = if card_1 > card_2 { 0 } else { 1 };
+                let [pile_1, pile_2] = piles;
+                decks[winner].extend(pile_1.into_iter().chain(pile_2));
+                break;
+            }
+            for _ in 0..3 {
+                for (deck, pile) in decks.iter_mut().zip(piles.iter_mut()) {
+                    let Some(card) = deck.pop_front() else {
+                        return Ok("PAT".to_string());
+                    };
+                    pile.push(card);
+                }
+            }
+        }
+    }
+    let winner = if decks[0].is_empty() { 2 } else { 1 };
+    Ok(format!("{} {}", winner, rounds))
+}
+
+/// the answer of the case must be the one the game gives, then the bot must give it
+fn run_winamax_battle(bot: &mut BotProcess, case: &Case) -> Result<String, String> {
+    let answer = play_winamax_battle(case)?;
+    if case.expected != [answer.as_str()] {
+        return Err(format!(
+            "the case expects `{}` but the game ends with `{}`",
+            case.expected.join(" / "),
+            answer
+        ));
+    }
+    run_output(bot, case)
+}
+
+fn run_shadows_of_the_knight(bot: &mut BotProcess, case: &Case) -> Result<String, String> {
+    let size = get_input_values(case, 0)?;
+    let max_turns = get_input_values(case, 1)?[0];
+    let start = get_input_values(case, 2)?;
+    let bomb = case.get_referee_values("bomb")?;
+    let (width, height) = (size[0], size[1]);
+    let (mut x, mut y) = (start[0], start[1]);
+
+    bot.send(&case.input);
+    for turn in 1..=max_turns {
+        let mut direction = String::new();
+        if bomb[1] < y {
+            direction.push('U');
+        } else if bomb[1] > y {
+            direction.push('D');
+        }
+        if bomb[0] < x {
+            direction.push('L');
+        } else if bomb[0] > x {
+            direction.push('R');
+        }
+        bot.send(&[direction]);
+        let answer = receive(bot, turn)?;
+        let jump = parse_numbers(&answer)?;
+        let [next_x, next_y] = jump[..] else {
+            return Err(format!("turn {}: bad jump `{}`", turn, answer));
+        };
+        if !(0..width).contains(&next_x) || !(0..height).contains(&next_y) {
+            return Err(format!(
+                "turn {}: jumped out of the building `{}`",
+                turn, answer
+            ));
+        }
+        (x, y) = (next_x, next_y);
+        if [x, y] == bomb[..] {
+            return Ok(format!("bomb found in {} turns", turn));
+        }
+    }
+    Err(format!("bomb not found in {} turns", max_turns))
+}
+
+/// the nodes linked together and the gateways of a Death First Search case
+struct Network {
+    links: Vec<Vec<usize>>,
+    gateways: Vec<usize>,
+}
+
+impl Network {
+    pub fn parse(case: &Case) -> Result<Self, String> {
+        let sizes = get_input_values(case, 0)?;
+        let [node_count, link_count, gateway_count] = sizes[..] else {
+            return Err("bad first input line".to_string());
+        };
+        let mut links = vec![Vec::new(); node_count as usize];
+        for index in 0..link_count as usize {
+            let link = get_input_values(case, 1 + index)?;
+            links[link[0] as usize].push(link[1] as usize);
+            links[link[1] as usize].push(link[0] as usize);
+        }
+        let mut gateways = Vec::new();
+        for index in 0..gateway_count as usize {
+            gateways.push(get_input_values(case, 1 + link_count as usize + index)?[0] as usize);
+        }
+        Ok(Self { links, gateways })
+    }
+
+    /// cuts the link, false if there is no such link
+    pub fn cut(&mut self, a: usize, b: usize) -> bool {
+        let (Some(a_links), true) = (self.links.get(a), b < self.links.len()) else {
+            return false;
+        };
+        if !a_links.contains(&b) {
+            return false;
+        }
+        self.links[a].retain(|node| *node != b);
+        self.links[b].retain(|node| *node != a);
+        true
+    }
+
+    pub fn count_gateway_links(&self, node: usize) -> usize {
+        self.links[node]
+            .iter()
+            .filter(|linked| self.gateways.contains(linked))
+            .count()
+    }
+
+    /// the next node of the agent, `None` once no gateway can be reached
+    pub fn get_agent_move(&self, agent: usize, prefer_gateway_links: bool) -> Option<usize> {
+        // distances to the closest gateway, the agent goes down this slope
+        let mut distances = vec![usize::MAX; self.links.len()];
+        let mut queue = VecDeque::new();
+        for gateway in &self.gateways {
+            distances[*gateway] = 0;
+            queue.push_back(*gateway);
+        }
+        while let Some(node) = queue.pop_front() {
+            for linked in &self.links[node] {
+                if distances[*linked] == usize::MAX {
+                    distances[*linked] = distances[node] + 1;
+                    queue.push_back(*linked);
+                }
+            }
+        }
+        if distances[agent] == usize::MAX {
+            return None;
+        }
+        let mut next_nodes = self.links[agent]
+            .iter()
+            .copied()
+            .filter(|linked| distances[*linked] + 1 == distances[agent])
+            .collect::<Vec<_>>();
+        next_nodes.sort();
+        if prefer_gateway_links {
+            next_nodes.sort_by_key(|node| std::cmp::Reverse(self.count_gateway_links(*node)));
+        }
+        next_nodes.first().copied()
+    }
+}
+
+fn run_death_first_search(
+    bot: &mut BotProcess,
+    case: &Case,
+    prefer_gateway_links: bool,
+) -> Result<String, String> {
+    let mut network = Network::parse(case)?;
+    let mut agent = case.get_referee_values("agent")?[0] as usize;
+    let max_turns = network.links.iter().map(Vec::len).sum::<usize>() as i32;
+
+    bot.send(&case.input);
+    for turn in 1..=max_turns {
+        bot.send(&[agent.to_string()]);
+        let answer = receive(bot, turn)?;
+        let link = parse_numbers(&answer)?;
+        let [a, b] = link[..] else {
+            return Err(format!("turn {}: bad link `{}`", turn, answer));
+        };
+        if a < 0 || b < 0 || !network.cut(a as usize, b as usize) {
+            return Err(format!("turn {}: no link `{}`", turn, answer));
+        }
+        let Some(next) = network.get_agent_move(agent, prefer_gateway_links) else {
+            return Ok(format!("agent blocked in {} turns", turn));
+        };
+        agent = next;
+        if network.gateways.contains(&agent) {
+            return Err(format!(
+                "turn {}: the agent reached gateway {}",
+                turn, agent
+            ));
+        }
+    }
+    Err(format!("the agent is still free after {} turns", max_turns))
+}
+
+/// a clone of Don't Panic, walking one cell per round
+#[derive(Clone, Copy)]
+struct Walker {
+    floor: i32,
+    x: i32,
+    direction: i32,
+}
+
+fn run_dont_panic(bot: &mut BotProcess, case: &Case) -> Result<String, String> {
+    let values = get_input_values(case, 0)?;
+    let [_, width, max_rounds, exit_floor, exit_x, total_clones, _, elevator_count] = values[..]
+    else {
+        return Err("bad first input line".to_string());
+    };
+    let mut elevators = HashSet::new();
+    for index in 0..elevator_count as usize {
+        let elevator = get_input_values(case, 1 + index)?;
+        elevators.insert((elevator[0], elevator[1]));
+    }
+    let generator = case.get_referee_values("generator")?[0];
+
+    let mut clones = VecDeque::<Walker>::new();
+    let mut blockers = HashSet::new();
+    let mut spawned = 0;
+    bot.send(&case.input);
+    for round in 1..=max_rounds {
+        if (round - 1) % CLONE_INTERVAL == 0 && spawned < total_clones {
+            // a clone coming out on a blocked clone turns around
+            let direction = if blockers.contains(&(0, generator)) {
+                -1
+            } else {
+                1
+            };
+            clones.push_back(Walker {
+                floor: 0,
+                x: generator,
+                direction,
+            });
+            spawned += 1;
+        }
+        let leading = clones.front().copied();
+        bot.send(&[match leading {
+            Some(clone) => format!(
+                "{} {} {}",
+                clone.floor,
+                clone.x,
+                if clone.direction > 0 { "RIGHT" } else { "LEFT" }
+            ),
+            None => "-1 -1 NONE".to_string(),
+        }]);
+        match receive(bot, round)?.as_str() {
+            "WAIT" => {}
+            "BLOCK" => {
+                let Some(clone) = clones.pop_front() else {
+                    return Err(format!("round {}: BLOCK without a clone", round));
+                };
+                blockers.insert((clone.floor, clone.x));
+            }
+            answer => return Err(format!("round {}: bad action `{}`", round, answer)),
+        }
+
+        for clone in clones.iter_mut() {
+            if elevators.contains(&(clone.floor, clone.x)) {
+                clone.floor += 1;
+                if blockers.contains(&(clone.floor, clone.x)) {
+                    clone.direction = -clone.direction;
+                }
+            } else if blockers.contains(&(clone.floor, clone.x + clone.direction)) {
+                clone.direction = -clone.direction;
+            } else {
+                clone.x += clone.direction;
+            }
+        }
+        if clones
+            .iter()
+            .any(|clone| (clone.floor, clone.x) == (exit_floor, exit_x))
+        {
+            return Ok(format!("a clone got out in {} rounds", round));
+        }
+        clones.retain(|clone| (0..width).contains(&clone.x));
+        if clones.is_empty() && spawned >= total_clones {
+            return Err(format!("round {}: no clone left", round));
+        }
+    }
+    Err(format!("no clone got out in {} rounds", max_rounds))
+}
+
+fn run_the_labyrinth(bot: &mut BotProcess, case: &Case) -> Result<String, String> {
+    let values = get_input_values(case, 0)?;
+    let [rows, columns, alarm] = values[..] else {
+        return Err("bad first input line".to_string());
+    };
+    let maze = case
+        .referee
+        .iter()
+        .map(|row| row.chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    if maze.len() != rows as usize || maze.iter().any(|row| row.len() != columns as usize) {
+        return Err(format!("the maze is not {}x{}", columns, rows));
+    }
+    let find = |target: char| {
+        (0..rows).find_map(|y| {
+            (0..columns)
+                .find(|x| maze[y as usize][*x as usize] == target)
+                .map(|x| (x, y))
+        })
+    };
+    let start = find('T').ok_or("no T in the maze")?;
+    let (mut x, mut y) = start;
+    let mut seen = vec![vec![false; columns as usize]; rows as usize];
+    let mut alarm_left: Option<i32> = None;
+
+    bot.send(&case.input);
+    for turn in 1..=LABYRINTH_FUEL {
+        for scan_y in (y - LABYRINTH_SCAN).max(0)..=(y + LABYRINTH_SCAN).min(rows - 1) {
+            for scan_x in (x - LABYRINTH_SCAN).max(0)..=(x + LABYRINTH_SCAN).min(columns - 1) {
+                seen[scan_y as usize][scan_x as usize] = true;
+            }
+        }
+        let mut lines = vec![format!("{} {}", y, x)];
+        for (row, seen_row) in maze.iter().zip(seen.iter()) {
+            lines.push(
+                row.iter()
+                    .zip(seen_row.iter())
+                    .map(|(cell, seen)| if *seen { *cell } else { '?' })
+                    .collect(),
+            );
+        }
+        bot.send(&lines);
+
+        let answer = receive(bot, turn)?;
+        match answer.as_str() {
+            "UP" => y -= 1,
+            "DOWN" => y += 1,
+            "LEFT" => x -= 1,
+            "RIGHT" => x += 1,
+            _ => return Err(format!("turn {}: bad move `{}`", turn, answer)),
+        }
+        let cell = maze
+            .get(y as usize)
+            .and_then(|row| row.get(x as usize))
+            .copied()
+            .unwrap_or('#');
+        if cell == '#' {
+            return Err(format!("turn {}: Kirk hit a wall at {} {}", turn, x, y));
+        }
+        if let Some(left) = alarm_left.as_mut() {
+            *left -= 1;
+            if (x, y) == start {
+                return Ok(format!("back at the start in {} turns", turn));
+            }
+            if *left <= 0 {
+                return Err(format!("turn {}: the alarm went off", turn));
+            }
+        } else if cell == 'C' {
+            alarm_left = Some(alarm);
+        }
+    }
+    Err(format!("out of fuel after {} turns", LABYRINTH_FUEL))
+}
+
+fn run_case(puzzle: &str, bot: &Path, case: &Case, verbose: bool) -> Result<String, String> {
+    let mut bot = BotProcess::spawn(&bot.to_string_lossy(), verbose);
+    match puzzle {
+        "winamax-battle" => run_winamax_battle(&mut bot, case),
+        "shadows-of-the-knight-episode-1" => run_shadows_of_the_knight(&mut bot, case),
+        "death-first-search-episode-1" => run_death_first_search(&mut bot, case, false),
+        "death-first-search-episode-2" => run_death_first_search(&mut bot, case, true),
+        "dont-panic-episode-1" => run_dont_panic(&mut bot, case),
+        "the-labyrinth" => run_the_labyrinth(&mut bot, case),
+        _ if !case.expected.is_empty() => run_output(&mut bot, case),
+        _ => Err(format!("no referee for {}", puzzle)),
+    }
+}
+
+/// the files of a directory, sorted by name
+fn read_dir_sorted(dir: &Path) -> Vec<PathBuf> {
+    let mut paths = fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("can not read {}: {}", dir.display(), e))
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+fn main() {
+    let mut verbose = false;
+    let mut bots_dir = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+    let mut cases_dir = PathBuf::from("tests/cases");
+    let mut puzzles = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--verbose" => verbose = true,
+            "--bots" => bots_dir = PathBuf::from(args.next().expect("--bots needs a directory")),
+            "--cases" => cases_dir = PathBuf::from(args.next().expect("--cases needs a directory")),
+            _ => puzzles.push(arg),
+        }
+    }
+    if puzzles.is_empty() {
+        puzzles = read_dir_sorted(&cases_dir)
+            .iter()
+            .filter(|path| path.is_dir())
+            .filter_map(|path| path.file_name()?.to_str().map(str::to_string))
+            .collect();
+    }
+
+    let (mut passed, mut failed) = (0, 0);
+    for puzzle in &puzzles {
+        let bot = bots_dir.join(puzzle);
+        for path in read_dir_sorted(&cases_dir.join(puzzle)) {
+            if path.extension().is_none_or(|extension| extension != "txt") {
+                continue;
+            }
+            let name = path.file_stem().unwrap_or_default().to_string_lossy();
+            let result = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| Case::parse(&content))
+                .and_then(|case| run_case(puzzle, &bot, &case, verbose));
+            match result {
+                Ok(summary) => {
+                    passed += 1;
+                    println!("ok   {}/{}: {}", puzzle, name, summary);
+                }
+                Err(reason) => {
+                    failed += 1;
+                    println!("FAIL {}/{}: {}", puzzle, name, reason);
+                }
+            }
+        }
+    }
+    println!("{} passed, {} failed", passed, failed);
+    if failed > 0 || passed == 0 {
+        std::process::exit(1);
+    }
+}
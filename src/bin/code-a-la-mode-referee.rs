@@ -0,0 +1,1082 @@
+// local referee for https://www.codingame.com/multiplayer/bot-programming/code-a-la-mode
+//
+// usage: code-a-la-mode-referee [--seed N] [--rounds N] [--turns N] [--kitchen FILE] [--tarts] [--verbose] <bot> [<partner>]
+//
+// a bot is either a command (quoted if it has arguments) talking over stdin/stdout,
+// or a scripted partner: `scripted:idle` (the default partner) stands still, `scripted:server` serves
+// the orders made of ice cream, blueberries and chopped strawberries (and steps aside when it is stuck)
+//
+// once the rounds are played, the referee prints for each chef the dishes it delivered, its duplicated orders (the
+// other chef served the customer its dish was made for) and wasted dishes (emptied at the dishwasher), and the
+// most turns in a row its moves did nothing
+//
+// rules implemented here:
+// - the kitchen is 11x7 (a `--kitchen` file may have another size), `0` and `1` are the chefs spawns, the other
+//   chef is an obstacle
+// - `MOVE x y` moves up to 4 cells (4 directions) toward the target, or next to it if it is not a floor cell
+// - `USE x y` interacts with the 8 cells around the chef, if the target is farther the chef moves toward it instead
+// - crates give their item (blueberries and ice cream can be added to a dish), the dishwasher gives a dish
+//   (or empties the one in hand), the chopping board chops strawberries and dough, chopped dough
+//   with blueberries is a raw tart, tables hold anything (and dishes take food from them)
+// - the oven bakes dough into a croissant and a raw tart into a tart in 10 turns, then burns it 10 turns later
+// - 3 customers are waiting at the same time, their award goes down by one each turn until they leave
+// - a dish matching a waiting customer order delivered at the window scores its award
+// - a game is 3 rounds of 200 turns, the kitchen is reset and the chefs swap spawns each round
+// - dishes are not limited
+
+use codinggame_backtocode_rust::process::BotProcess;
+use codinggame_backtocode_rust::random::Random;
+use std::env;
+use std::fs;
+
+const DEFAULT_KITCHEN: [&str; 7] = [
+    "#####D##B##",
+    "#0........#",
+    "#.####.##.I",
+    "S.#C.#..#.#",
+    "#.##.####.#",
+    "#........1#",
+    "####W##OH##",
+];
+const ROUNDS: i32 = 3;
+const TURNS: i32 = 200;
+const MAX_MOVES: i32 = 4;
+const CUSTOMERS_COUNT: usize = 20;
+const WAITING_CUSTOMERS: usize = 3;
+const BAKING_TURNS: i32 = 10;
+const BURNING_TURNS: i32 = 10;
+
+trait Agent {
+    /// sends lines to the bot
+    fn send(&mut self, lines: &[String]);
+    /// reads the action of the turn, `None` if the bot died
+    fn receive(&mut self) -> Option<String>;
+}
+
+impl Agent for BotProcess {
+    fn send(&mut self, lines: &[String]) {
+        BotProcess::send(self, lines);
+    }
+
+    fn receive(&mut self) -> Option<String> {
+        BotProcess::receive(self)
+    }
+}
+
+/// a partner standing still
+struct IdleAgent;
+
+impl Agent for IdleAgent {
+    fn send(&mut self, _lines: &[String]) {}
+
+    fn receive(&mut self) -> Option<String> {
+        Some("WAIT".into())
+    }
+}
+
+/// a partner serving the orders without croissant and tart, reading the same lines as a bot
+#[derive(Default)]
+struct ServerAgent {
+    kitchen: Option<Kitchen>,
+    lines: Vec<String>,
+    /// our position and answer last turn, and the turns in a row they did not change
+    last: Option<([i32; 2], String)>,
+    stuck_turns: i32,
+}
+
+impl ServerAgent {
+    /// the closest table (by distance) a chef can reach and put something on
+    fn find_empty_table(
+        &self,
+        from: [i32; 2],
+        tables: &[([i32; 2], Vec<ItemType>)],
+    ) -> Option<[i32; 2]> {
+        let kitchen = self.kitchen.as_ref()?;
+        (0..kitchen.width * kitchen.height)
+            .map(|index| [index % kitchen.width, index / kitchen.width])
+            .filter(|pos| kitchen.get_cell(*pos) == Some(CellType::Table))
+            .filter(|pos| !tables.iter().any(|(table, _)| table == pos))
+            .filter(|pos| {
+                [[-1, 0], [1, 0], [0, -1], [0, 1]].iter().any(|[dx, dy]| {
+                    kitchen.get_cell([pos[0] + dx, pos[1] + dy]) == Some(CellType::Floor)
+                })
+            })
+            .min_by_key(|pos| (pos[0] - from[0]).abs() + (pos[1] - from[1]).abs())
+    }
+
+    /// the position of the first cell of the kitchen with this type
+    fn find_cell(&self, cell_type: CellType) -> Option<[i32; 2]> {
+        let kitchen = self.kitchen.as_ref()?;
+        let index = kitchen.cells.iter().position(|cell| *cell == cell_type)? as i32;
+        Some([index % kitchen.width, index / kitchen.width])
+    }
+
+    /// the floor cell next to us farthest from the other chef (then with the most ways out), to let it out of a
+    /// corridor
+    fn find_step_aside(&self) -> Option<[i32; 2]> {
+        let kitchen = self.kitchen.as_ref()?;
+        let (pos, _) = decode_chef(self.lines.get(1)?)?;
+        let (partner, _) = decode_chef(self.lines.get(2)?)?;
+        let is_free = |[x, y]: [i32; 2]| {
+            [x, y] != partner && kitchen.get_cell([x, y]) == Some(CellType::Floor)
+        };
+        let neighbors = |[x, y]: [i32; 2]| [[x - 1, y], [x + 1, y], [x, y - 1], [x, y + 1]];
+        neighbors(pos)
+            .into_iter()
+            .filter(|next| is_free(*next))
+            .max_by_key(|next| {
+                let distance = (next[0] - partner[0]).abs() + (next[1] - partner[1]).abs();
+                let ways_out = neighbors(*next).into_iter().filter(|n| is_free(*n)).count();
+                (distance, ways_out)
+            })
+    }
+
+    fn get_action(&self) -> Option<String> {
+        let lines = &self.lines;
+        let (pos, hand) = decode_chef(lines.get(1)?)?;
+        let tables_count = lines.get(3)?.parse::<usize>().ok()?;
+        let tables = lines[4..4 + tables_count]
+            .iter()
+            .filter_map(|line| decode_chef(line))
+            .collect::<Vec<_>>();
+        let orders = lines[4 + tables_count + 2..]
+            .iter()
+            .map(|line| ItemType::decode(line.split_whitespace().next().unwrap_or("NONE")))
+            .filter(|order| {
+                !order
+                    .iter()
+                    .any(|item| matches!(item, ItemType::Croissant | ItemType::Tart))
+            })
+            .collect::<Vec<_>>();
+        let table_with = |items: &[ItemType]| {
+            tables
+                .iter()
+                .find(|(_, table)| ItemType::is_same_dish(table, items))
+                .map(|(pos, _)| *pos)
+        };
+        let use_cell = |target: Option<[i32; 2]>| target.map(|[x, y]| format!("USE {} {}", x, y));
+
+        // the smallest order, done with the dish we hold if it fits one
+        let Some(order) = orders
+            .iter()
+            .filter(|order| {
+                hand.first() != Some(&ItemType::Dish)
+                    || hand.iter().all(|item| order.contains(item))
+            })
+            .min_by_key(|order| order.len())
+        else {
+            return match hand[..] {
+                [] | [ItemType::Dish] => Some("WAIT".into()),
+                [ItemType::Dish, ..] => use_cell(self.find_cell(CellType::DishWasher)),
+                _ => use_cell(self.find_empty_table(pos, &tables)),
+            };
+        };
+        let chopped = [ItemType::ChoppedStrawberries];
+        let needs_chopped = order.contains(&ItemType::ChoppedStrawberries)
+            && !hand.contains(&ItemType::ChoppedStrawberries)
+            && table_with(&chopped).is_none();
+        match hand[..] {
+            [ItemType::Strawberries] => use_cell(self.find_cell(CellType::Chopper)),
+            [ItemType::ChoppedStrawberries] => use_cell(self.find_empty_table(pos, &tables)),
+            [] if needs_chopped => {
+                use_cell(self.find_cell(CellType::Crate(ItemType::Strawberries)))
+            }
+            [] => use_cell(self.find_cell(CellType::DishWasher)),
+            _ if needs_chopped => use_cell(self.find_empty_table(pos, &tables)),
+            _ => match order.iter().find(|item| !hand.contains(item)) {
+                None => use_cell(self.find_cell(CellType::Window)),
+                Some(ItemType::ChoppedStrawberries) => use_cell(table_with(&chopped)),
+                Some(item) => use_cell(self.find_cell(CellType::Crate(*item))),
+            },
+        }
+    }
+}
+
+/// the position and hand of a chef line
+fn decode_chef(line: &str) -> Option<([i32; 2], Vec<ItemType>)> {
+    let inputs = line.split_whitespace().collect::<Vec<_>>();
+    let pos = [inputs.first()?.parse().ok()?, inputs.get(1)?.parse().ok()?];
+    Some((pos, ItemType::decode(inputs.get(2)?)))
+}
+
+impl Agent for ServerAgent {
+    fn send(&mut self, lines: &[String]) {
+        if self.kitchen.is_none() {
+            // initial lines: the customers then the kitchen rows
+            let customers = lines[0].parse::<usize>().unwrap_or(0);
+            self.kitchen = Some(Kitchen::new(lines[1 + customers..].to_vec()));
+            return;
+        }
+        self.lines = lines.to_vec();
+    }
+
+    fn receive(&mut self) -> Option<String> {
+        let action = self.get_action().unwrap_or_else(|| "WAIT".into());
+        let pos = self
+            .lines
+            .get(1)
+            .and_then(|line| decode_chef(line))
+            .map(|(pos, _)| pos);
+        let current = pos.map(|pos| (pos, action.clone()));
+        if action != "WAIT" && current.is_some() && self.last == current {
+            self.stuck_turns += 1;
+        } else {
+            self.stuck_turns = 0;
+        }
+        self.last = current;
+        if self.stuck_turns >= 2 {
+            if let Some([x, y]) = self.find_step_aside() {
+                return Some(format!("MOVE {} {}", x, y));
+            }
+        }
+        Some(action)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum ItemType {
+    Dish,
+    Blueberries,
+    IceCream,
+    Strawberries,
+    ChoppedStrawberries,
+    Croissant,
+    Dough,
+    ChoppedDough,
+    RawTart,
+    Tart,
+}
+
+impl ItemType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ItemType::Dish => "DISH",
+            ItemType::Blueberries => "BLUEBERRIES",
+            ItemType::IceCream => "ICE_CREAM",
+            ItemType::Strawberries => "STRAWBERRIES",
+            ItemType::ChoppedStrawberries => "CHOPPED_STRAWBERRIES",
+            ItemType::Croissant => "CROISSANT",
+            ItemType::Dough => "DOUGH",
+            ItemType::ChoppedDough => "CHOPPED_DOUGH",
+            ItemType::RawTart => "RAW_TART",
+            ItemType::Tart => "TART",
+        }
+    }
+
+    /// food a dish can hold
+    fn is_dessert(&self) -> bool {
+        matches!(
+            self,
+            ItemType::Blueberries
+                | ItemType::IceCream
+                | ItemType::ChoppedStrawberries
+                | ItemType::Croissant
+                | ItemType::Tart
+        )
+    }
+
+    /// how many points a customer gives for this dessert
+    fn get_award(&self) -> i32 {
+        match self {
+            ItemType::Blueberries => 150,
+            ItemType::IceCream => 150,
+            ItemType::ChoppedStrawberries => 300,
+            ItemType::Croissant => 500,
+            ItemType::Tart => 800,
+            _ => 0,
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "DISH" => Some(ItemType::Dish),
+            "BLUEBERRIES" => Some(ItemType::Blueberries),
+            "ICE_CREAM" => Some(ItemType::IceCream),
+            "STRAWBERRIES" => Some(ItemType::Strawberries),
+            "CHOPPED_STRAWBERRIES" => Some(ItemType::ChoppedStrawberries),
+            "CROISSANT" => Some(ItemType::Croissant),
+            "DOUGH" => Some(ItemType::Dough),
+            "CHOPPED_DOUGH" => Some(ItemType::ChoppedDough),
+            "RAW_TART" => Some(ItemType::RawTart),
+            "TART" => Some(ItemType::Tart),
+            _ => None,
+        }
+    }
+
+    /// items of a `-` separated list, `NONE` is an empty one
+    fn decode(str: &str) -> Vec<Self> {
+        str.split('-').filter_map(ItemType::from_str).collect()
+    }
+
+    fn encode(items: &[Self]) -> String {
+        if items.is_empty() {
+            return "NONE".into();
+        }
+        items
+            .iter()
+            .map(|item| item.as_str())
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    /// what we hold after taking the item, `None` if both can not go together
+    fn combine(hand: &[Self], item: Self) -> Option<Vec<Self>> {
+        if hand.is_empty() {
+            return Some(vec![item]);
+        }
+        if hand[0] == ItemType::Dish && item.is_dessert() && !hand.contains(&item) {
+            let mut items = hand.to_vec();
+            items.push(item);
+            return Some(items);
+        }
+        if hand == [ItemType::ChoppedDough] && item == ItemType::Blueberries {
+            return Some(vec![ItemType::RawTart]);
+        }
+        None
+    }
+
+    /// same desserts, whatever the order
+    fn is_same_dish(a: &[Self], b: &[Self]) -> bool {
+        a.len() == b.len() && a.iter().all(|item| b.contains(item))
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum CellType {
+    Floor,
+    Table,
+    DishWasher,
+    Window,
+    Crate(ItemType),
+    Chopper,
+    Oven,
+}
+
+impl CellType {
+    fn from_char(c: char) -> Self {
+        match c {
+            '.' | '0' | '1' => CellType::Floor,
+            'D' => CellType::DishWasher,
+            'W' => CellType::Window,
+            'B' => CellType::Crate(ItemType::Blueberries),
+            'I' => CellType::Crate(ItemType::IceCream),
+            'S' => CellType::Crate(ItemType::Strawberries),
+            'H' => CellType::Crate(ItemType::Dough),
+            'C' => CellType::Chopper,
+            'O' => CellType::Oven,
+            _ => CellType::Table,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Customer {
+    order: Vec<ItemType>,
+    award: i32,
+}
+
+impl Customer {
+    fn encode(&self) -> String {
+        format!("{} {}", ItemType::encode(&self.order), self.award)
+    }
+}
+
+/// every customer of the game, a dish and 1 to 4 desserts
+fn generate_customers(random: &mut Random, with_tarts: bool) -> Vec<Customer> {
+    let mut desserts = vec![
+        ItemType::IceCream,
+        ItemType::Blueberries,
+        ItemType::ChoppedStrawberries,
+        ItemType::Croissant,
+    ];
+    if with_tarts {
+        desserts.push(ItemType::Tart);
+    }
+    (0..CUSTOMERS_COUNT)
+        .map(|_| {
+            let mut order = vec![ItemType::Dish];
+            let count = random.range(1, 5);
+            while order.len() <= count as usize {
+                let dessert = desserts[random.range(0, desserts.len() as i32) as usize];
+                if !order.contains(&dessert) {
+                    order.push(dessert);
+                }
+            }
+            let award =
+                order.iter().map(|item| item.get_award()).sum::<i32>() + random.range(0, 20) * 10;
+            Customer { order, award }
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+struct Chef {
+    pos: [i32; 2],
+    hand: Vec<ItemType>,
+}
+
+struct Kitchen {
+    rows: Vec<String>,
+    width: i32,
+    height: i32,
+    cells: Vec<CellType>,
+    spawns: [[i32; 2]; 2],
+}
+
+impl Kitchen {
+    pub fn new(rows: Vec<String>) -> Self {
+        let width = rows.first().map_or(0, |row| row.chars().count()) as i32;
+        let height = rows.len() as i32;
+        let mut cells = Vec::with_capacity((width * height) as usize);
+        let mut spawns = [[0, 0]; 2];
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                cells.push(CellType::from_char(c));
+                if let Some(id) = c.to_digit(10).filter(|id| *id < 2) {
+                    spawns[id as usize] = [x as i32, y as i32];
+                }
+            }
+        }
+        assert_eq!(
+            cells.len(),
+            (width * height) as usize,
+            "kitchen rows have the same width"
+        );
+        Self {
+            rows,
+            width,
+            height,
+            cells,
+            spawns,
+        }
+    }
+
+    pub fn get_cell(&self, pos: [i32; 2]) -> Option<CellType> {
+        if !(0..self.width).contains(&pos[0]) || !(0..self.height).contains(&pos[1]) {
+            return None;
+        }
+        self.cells
+            .get((pos[1] * self.width + pos[0]) as usize)
+            .copied()
+    }
+
+    pub fn is_adjacent(a: [i32; 2], b: [i32; 2]) -> bool {
+        a != b && (a[0] - b[0]).abs() <= 1 && (a[1] - b[1]).abs() <= 1
+    }
+}
+
+#[derive(Default)]
+struct Oven {
+    content: Option<ItemType>,
+    timer: i32,
+}
+
+impl Oven {
+    /// bakes, then burns
+    pub fn update(&mut self) {
+        let Some(content) = self.content else {
+            return;
+        };
+        self.timer -= 1;
+        if self.timer > 0 {
+            return;
+        }
+        match content {
+            ItemType::Dough => {
+                self.content = Some(ItemType::Croissant);
+                self.timer = BURNING_TURNS;
+            }
+            ItemType::RawTart => {
+                self.content = Some(ItemType::Tart);
+                self.timer = BURNING_TURNS;
+            }
+            _ => {
+                self.content = None;
+                self.timer = 0;
+            }
+        }
+    }
+
+    pub fn is_baked(&self) -> bool {
+        matches!(self.content, Some(ItemType::Croissant | ItemType::Tart))
+    }
+}
+
+enum Action {
+    Move([i32; 2]),
+    Use([i32; 2]),
+    Wait,
+}
+
+impl Action {
+    fn decode(str: &str) -> Option<Self> {
+        let inputs = str.split_whitespace().collect::<Vec<_>>();
+        let action = *inputs.first()?;
+        if action == "WAIT" {
+            return Some(Action::Wait);
+        }
+        let pos = [inputs.get(1)?.parse().ok()?, inputs.get(2)?.parse().ok()?];
+        match action {
+            "MOVE" => Some(Action::Move(pos)),
+            "USE" => Some(Action::Use(pos)),
+            _ => None,
+        }
+    }
+}
+
+/// a round: the kitchen state shared by both chefs
+struct Round {
+    chefs: Vec<Chef>,
+    tables: Vec<([i32; 2], Vec<ItemType>)>,
+    oven: Oven,
+    /// customers not served yet, the first ones are waiting
+    queue: Vec<Customer>,
+    score: i32,
+}
+
+/// what a chef did over the game, to spot chefs getting in each other's way
+#[derive(Default)]
+struct ChefStats {
+    delivered: i32,
+    /// the other chef served the order our dish was made for, no other waiting customer wants that dish
+    duplicated_orders: i32,
+    /// dishes with food emptied at the dishwasher, their customer was served (or left) before
+    wasted_dishes: i32,
+    /// turns in a row the chef moved or used something without moving nor changing its hand (waiting does not count),
+    /// the current run and the longest one
+    stuck_turns: i32,
+    longest_stuck: i32,
+}
+
+struct Referee {
+    kitchen: Kitchen,
+    customers: Vec<Customer>,
+    turns: i32,
+    round: Round,
+    stats: [ChefStats; 2],
+}
+
+impl Referee {
+    pub fn new(kitchen: Kitchen, customers: Vec<Customer>, turns: i32) -> Self {
+        let round = Referee::new_round(&kitchen, &customers, 0);
+        Self {
+            kitchen,
+            customers,
+            turns,
+            round,
+            stats: Default::default(),
+        }
+    }
+
+    fn new_round(kitchen: &Kitchen, customers: &[Customer], index: i32) -> Round {
+        let spawns = if index % 2 == 0 {
+            kitchen.spawns
+        } else {
+            [kitchen.spawns[1], kitchen.spawns[0]]
+        };
+        Round {
+            chefs: spawns
+                .iter()
+                .map(|pos| Chef {
+                    pos: *pos,
+                    hand: Vec::new(),
+                })
+                .collect(),
+            tables: Vec::new(),
+            oven: Oven::default(),
+            queue: customers.to_vec(),
+            score: 0,
+        }
+    }
+
+    /// what a bot reads once, before the first round
+    pub fn get_initial_lines(&self) -> Vec<String> {
+        let mut lines = vec![self.customers.len().to_string()];
+        lines.extend(self.customers.iter().map(|customer| customer.encode()));
+        lines.extend(self.kitchen.rows.iter().cloned());
+        lines
+    }
+
+    pub fn get_turn_lines(&self, chef_id: usize, turns_remaining: i32) -> Vec<String> {
+        let chef = &self.round.chefs[chef_id];
+        let partner = &self.round.chefs[1 - chef_id];
+        let mut lines = vec![
+            turns_remaining.to_string(),
+            format!(
+                "{} {} {}",
+                chef.pos[0],
+                chef.pos[1],
+                ItemType::encode(&chef.hand)
+            ),
+            format!(
+                "{} {} {}",
+                partner.pos[0],
+                partner.pos[1],
+                ItemType::encode(&partner.hand)
+            ),
+            self.round.tables.len().to_string(),
+        ];
+        for (pos, items) in &self.round.tables {
+            lines.push(format!("{} {} {}", pos[0], pos[1], ItemType::encode(items)));
+        }
+        lines.push(format!(
+            "{} {}",
+            self.round
+                .oven
+                .content
+                .map_or("NONE", |content| content.as_str()),
+            self.round.oven.timer
+        ));
+        let waiting = &self.round.queue[..self.round.queue.len().min(WAITING_CUSTOMERS)];
+        lines.push(waiting.len().to_string());
+        lines.extend(waiting.iter().map(|customer| customer.encode()));
+        lines
+    }
+
+    /// floor cells reachable within `MAX_MOVES` cells toward the target (4 directions, the partner blocks)
+    fn get_next_position(&self, chef_id: usize, target: [i32; 2]) -> [i32; 2] {
+        let start = self.round.chefs[chef_id].pos;
+        let partner = self.round.chefs[1 - chef_id].pos;
+        let is_floor =
+            |pos: [i32; 2]| pos != partner && self.kitchen.get_cell(pos) == Some(CellType::Floor);
+        let is_goal = |pos: [i32; 2]| {
+            if self.kitchen.get_cell(target) == Some(CellType::Floor) {
+                pos == target
+            } else {
+                Kitchen::is_adjacent(pos, target)
+            }
+        };
+
+        // bfs keeping parents to walk back the path
+        let width = self.kitchen.width;
+        let mut parents = vec![None; (width * self.kitchen.height) as usize];
+        let index = |pos: [i32; 2]| (pos[1] * width + pos[0]) as usize;
+        parents[index(start)] = Some(start);
+        let mut queue = std::collections::VecDeque::from([start]);
+        let mut goal = None;
+        while let Some(pos) = queue.pop_front() {
+            if is_goal(pos) {
+                goal = Some(pos);
+                break;
+            }
+            for next in [
+                [pos[0] - 1, pos[1]],
+                [pos[0] + 1, pos[1]],
+                [pos[0], pos[1] - 1],
+                [pos[0], pos[1] + 1],
+            ] {
+                if !is_floor(next) || parents[index(next)].is_some() {
+                    continue;
+                }
+                parents[index(next)] = Some(pos);
+                queue.push_back(next);
+            }
+        }
+
+        let Some(goal) = goal else {
+            return start;
+        };
+        let mut path = vec![goal];
+        while let Some(parent) = parents[index(*path.last().unwrap())] {
+            if parent == *path.last().unwrap() {
+                break;
+            }
+            path.push(parent);
+        }
+        path.reverse();
+        path[(path.len() - 1).min(MAX_MOVES as usize)]
+    }
+
+    fn use_cell(&mut self, chef_id: usize, target: [i32; 2]) {
+        let Some(cell_type) = self.kitchen.get_cell(target) else {
+            return;
+        };
+        let round = &mut self.round;
+        let hand = round.chefs[chef_id].hand.clone();
+        let new_hand = match cell_type {
+            CellType::Floor => None,
+            CellType::Crate(item) => ItemType::combine(&hand, item),
+            CellType::DishWasher => match hand.first() {
+                None | Some(ItemType::Dish) => {
+                    if hand.len() > 1 {
+                        self.stats[chef_id].wasted_dishes += 1;
+                    }
+                    Some(vec![ItemType::Dish])
+                }
+                _ => None,
+            },
+            CellType::Chopper => match hand[..] {
+                [ItemType::Strawberries] => Some(vec![ItemType::ChoppedStrawberries]),
+                [ItemType::Dough] => Some(vec![ItemType::ChoppedDough]),
+                _ => None,
+            },
+            CellType::Oven => {
+                if round.oven.content.is_none() {
+                    match hand[..] {
+                        [item @ (ItemType::Dough | ItemType::RawTart)] => {
+                            round.oven.content = Some(item);
+                            round.oven.timer = BAKING_TURNS;
+                            Some(Vec::new())
+                        }
+                        _ => None,
+                    }
+                } else if round.oven.is_baked() {
+                    let new_hand = ItemType::combine(&hand, round.oven.content.unwrap());
+                    if new_hand.is_some() {
+                        round.oven.content = None;
+                        round.oven.timer = 0;
+                    }
+                    new_hand
+                } else {
+                    None
+                }
+            }
+            CellType::Window => {
+                let position = round.queue[..round.queue.len().min(WAITING_CUSTOMERS)]
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, customer)| ItemType::is_same_dish(&customer.order, &hand))
+                    .max_by_key(|(_, customer)| customer.award)
+                    .map(|(position, _)| position);
+                position.map(|position| {
+                    let customer = round.queue.remove(position);
+                    round.score += customer.award;
+                    self.stats[chef_id].delivered += 1;
+                    let other_hand = &round.chefs[1 - chef_id].hand;
+                    let fits =
+                        |order: &[ItemType]| other_hand.iter().all(|item| order.contains(item));
+                    let waiting = &round.queue[..round.queue.len().min(WAITING_CUSTOMERS)];
+                    if other_hand.len() > 1
+                        && fits(&customer.order)
+                        && !waiting.iter().any(|customer| fits(&customer.order))
+                    {
+                        self.stats[1 - chef_id].duplicated_orders += 1;
+                    }
+                    Vec::new()
+                })
+            }
+            CellType::Table => {
+                let table = round.tables.iter().position(|(pos, _)| *pos == target);
+                match table {
+                    None if !hand.is_empty() => {
+                        round.tables.push((target, hand.clone()));
+                        Some(Vec::new())
+                    }
+                    None => None,
+                    Some(table) => {
+                        let items = round.tables[table].1.clone();
+                        if hand.is_empty() {
+                            round.tables.swap_remove(table);
+                            Some(items)
+                        } else if let Some(new_hand) = match items[..] {
+                            [item] => ItemType::combine(&hand, item),
+                            _ => None,
+                        } {
+                            round.tables.swap_remove(table);
+                            Some(new_hand)
+                        } else if let Some(new_items) = match hand[..] {
+                            [item] => ItemType::combine(&items, item),
+                            _ => None,
+                        } {
+                            round.tables[table].1 = new_items;
+                            Some(Vec::new())
+                        } else {
+                            None
+                        }
+                    }
+                }
+            }
+        };
+        if let Some(new_hand) = new_hand {
+            round.chefs[chef_id].hand = new_hand;
+        }
+    }
+
+    fn play_action(&mut self, chef_id: usize, action: Action) {
+        let before = self.round.chefs[chef_id].clone();
+        match action {
+            Action::Wait => return,
+            Action::Move(target) => {
+                self.round.chefs[chef_id].pos = self.get_next_position(chef_id, target);
+            }
+            Action::Use(target) => {
+                if Kitchen::is_adjacent(self.round.chefs[chef_id].pos, target) {
+                    self.use_cell(chef_id, target);
+                } else {
+                    self.round.chefs[chef_id].pos = self.get_next_position(chef_id, target);
+                }
+            }
+        }
+        let chef = &self.round.chefs[chef_id];
+        let stats = &mut self.stats[chef_id];
+        if chef.pos == before.pos && chef.hand == before.hand {
+            stats.stuck_turns += 1;
+            stats.longest_stuck = stats.longest_stuck.max(stats.stuck_turns);
+        } else {
+            stats.stuck_turns = 0;
+        }
+    }
+
+    /// plays a whole round, returns its score
+    pub fn play_round(&mut self, index: i32, agents: &mut [Box<dyn Agent>]) -> i32 {
+        self.round = Referee::new_round(&self.kitchen, &self.customers, index);
+        for stats in self.stats.iter_mut() {
+            stats.stuck_turns = 0;
+        }
+        for turn in 0..self.turns {
+            let turns_remaining = self.turns - turn;
+            // chefs play one after the other, the first one changes every turn
+            for i in 0..2 {
+                let chef_id = (i + turn as usize) % 2;
+                agents[chef_id].send(&self.get_turn_lines(chef_id, turns_remaining));
+                let Some(answer) = agents[chef_id].receive() else {
+                    continue;
+                };
+                match Action::decode(&answer) {
+                    Some(action) => self.play_action(chef_id, action),
+                    None => eprintln!("chef {}: invalid action {}", chef_id, answer),
+                }
+            }
+
+            self.round.oven.update();
+            for customer in self.round.queue.iter_mut().take(WAITING_CUSTOMERS) {
+                customer.award -= 1;
+            }
+            self.round.queue.retain(|customer| customer.award > 0);
+            if self.round.queue.is_empty() {
+                break;
+            }
+        }
+        self.round.score
+    }
+}
+
+fn main() {
+    let mut seed = 0;
+    let mut rounds = ROUNDS;
+    let mut turns = TURNS;
+    let mut kitchen_rows = DEFAULT_KITCHEN
+        .iter()
+        .map(|row| row.to_string())
+        .collect::<Vec<_>>();
+    let mut with_tarts = false;
+    let mut verbose = false;
+    let mut bots = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = args.next().expect("--seed needs a value").parse().unwrap(),
+            "--rounds" => {
+                rounds = args
+                    .next()
+                    .expect("--rounds needs a value")
+                    .parse()
+                    .unwrap()
+            }
+            "--turns" => turns = args.next().expect("--turns needs a value").parse().unwrap(),
+            "--kitchen" => {
+                let path = args.next().expect("--kitchen needs a file");
+                kitchen_rows = fs::read_to_string(&path)
+                    .unwrap_or_else(|e| panic!("can not read {}: {}", path, e))
+                    .lines()
+                    .map(|row| row.to_string())
+                    .collect();
+            }
+            "--tarts" => with_tarts = true,
+            "--verbose" => verbose = true,
+            _ => bots.push(arg),
+        }
+    }
+    if bots.is_empty() || bots.len() > 2 {
+        eprintln!("usage: code-a-la-mode-referee [--seed N] [--rounds N] [--turns N] [--kitchen FILE] [--tarts] [--verbose] <bot> [<partner>]");
+        eprintln!("a bot is a command, scripted:idle or scripted:server");
+        std::process::exit(2);
+    }
+    if bots.len() == 1 {
+        bots.push("scripted:idle".into());
+    }
+
+    let mut agents: Vec<Box<dyn Agent>> = bots
+        .iter()
+        .map(|bot| -> Box<dyn Agent> {
+            match bot.strip_prefix("scripted:") {
+                Some("idle") => Box::new(IdleAgent),
+                Some("server") => Box::new(ServerAgent::default()),
+                Some(name) => panic!("unknown scripted bot: {}", name),
+                None => Box::new(BotProcess::spawn(bot, verbose)),
+            }
+        })
+        .collect();
+
+    let mut random = Random::new(seed);
+    let customers = generate_customers(&mut random, with_tarts);
+    let mut referee = Referee::new(Kitchen::new(kitchen_rows), customers, turns);
+    let initial_lines = referee.get_initial_lines();
+    for agent in agents.iter_mut() {
+        agent.send(&initial_lines);
+    }
+
+    let mut total = 0;
+    for index in 0..rounds {
+        let score = referee.play_round(index, &mut agents);
+        println!("round {}: {}", index + 1, score);
+        total += score;
+    }
+    for (chef_id, stats) in referee.stats.iter().enumerate() {
+        println!(
+            "chef {}: {} delivered, {} duplicated orders, {} wasted dishes, stuck {} turns in a row at most",
+            chef_id, stats.delivered, stats.duplicated_orders, stats.wasted_dishes, stats.longest_stuck
+        );
+    }
+    println!("total: {}", total);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ItemType::*;
+
+    /// the default kitchen, chef 0 at 1,1 and chef 1 at 9,5
+    fn get_referee(customers: Vec<Customer>) -> Referee {
+        let rows = DEFAULT_KITCHEN.iter().map(|row| row.to_string()).collect();
+        Referee::new(Kitchen::new(rows), customers, TURNS)
+    }
+
+    #[test]
+    fn chefs_move_up_to_4_cells_around_the_partner() {
+        let mut referee = get_referee(Vec::new());
+        assert_eq!(referee.get_next_position(0, [9, 1]), [5, 1]);
+        // next to a cell that is not a floor
+        assert_eq!(referee.get_next_position(0, [5, 0]), [4, 1]);
+        // the partner blocks the corridor, the way around goes down
+        referee.round.chefs[1].pos = [3, 1];
+        assert_eq!(referee.get_next_position(0, [9, 1]), [1, 5]);
+        // nowhere to go
+        referee.round.chefs[1].pos = [1, 2];
+        referee.round.chefs[0].pos = [1, 1];
+        assert_eq!(referee.get_next_position(0, [1, 1]), [1, 1]);
+    }
+
+    #[test]
+    fn the_oven_bakes_then_burns() {
+        let mut oven = Oven {
+            content: Some(Dough),
+            timer: BAKING_TURNS,
+        };
+        for _ in 1..BAKING_TURNS {
+            oven.update();
+        }
+        assert_eq!(oven.content, Some(Dough));
+        oven.update();
+        assert!(oven.is_baked());
+        assert_eq!((oven.content, oven.timer), (Some(Croissant), BURNING_TURNS));
+        for _ in 0..BURNING_TURNS {
+            oven.update();
+        }
+        assert_eq!((oven.content, oven.timer), (None, 0));
+    }
+
+    #[test]
+    fn the_oven_takes_a_raw_tart_and_gives_back_a_tart_on_a_dish() {
+        let mut referee = get_referee(Vec::new());
+        let oven = [7, 6];
+        referee.round.chefs[0].pos = [7, 5];
+        referee.round.chefs[0].hand = vec![Dish];
+        // a dish can not go in the oven
+        referee.use_cell(0, oven);
+        assert_eq!(referee.round.oven.content, None);
+        referee.round.chefs[0].hand = vec![RawTart];
+        referee.use_cell(0, oven);
+        assert_eq!(referee.round.oven.content, Some(RawTart));
+        assert!(referee.round.chefs[0].hand.is_empty());
+        for _ in 0..BAKING_TURNS {
+            referee.round.oven.update();
+        }
+        referee.round.chefs[0].hand = vec![Dish, IceCream];
+        referee.use_cell(0, oven);
+        assert_eq!(referee.round.chefs[0].hand, vec![Dish, IceCream, Tart]);
+        assert_eq!(referee.round.oven.content, None);
+    }
+
+    #[test]
+    fn tables_hold_items_and_combine_them() {
+        let mut referee = get_referee(Vec::new());
+        let table = [0, 1];
+        referee.round.chefs[0].hand = vec![Dish];
+        referee.use_cell(0, table);
+        assert_eq!(referee.round.tables, vec![(table, vec![Dish])]);
+        // food goes on the dish of the table
+        referee.round.chefs[0].hand = vec![Blueberries];
+        referee.use_cell(0, table);
+        assert_eq!(referee.round.tables, vec![(table, vec![Dish, Blueberries])]);
+        assert!(referee.round.chefs[0].hand.is_empty());
+        // the same food twice does not fit
+        referee.round.chefs[0].hand = vec![Blueberries];
+        referee.use_cell(0, table);
+        assert_eq!(referee.round.chefs[0].hand, vec![Blueberries]);
+        // an empty hand takes everything
+        referee.round.chefs[0].hand = Vec::new();
+        referee.use_cell(0, table);
+        assert_eq!(referee.round.chefs[0].hand, vec![Dish, Blueberries]);
+        assert!(referee.round.tables.is_empty());
+
+        // a dish in hand takes the food of the table, blueberries make a raw tart with chopped dough
+        referee.round.tables.push((table, vec![IceCream]));
+        referee.round.chefs[0].hand = vec![Dish];
+        referee.use_cell(0, table);
+        assert_eq!(referee.round.chefs[0].hand, vec![Dish, IceCream]);
+        referee.round.tables.push((table, vec![Blueberries]));
+        referee.round.chefs[0].hand = vec![ChoppedDough];
+        referee.use_cell(0, table);
+        assert_eq!(referee.round.chefs[0].hand, vec![RawTart]);
+        assert!(referee.round.tables.is_empty());
+    }
+
+    #[test]
+    fn waiting_customers_lose_a_point_each_turn_then_leave() {
+        let customer = |award| Customer {
+            order: vec![Dish, IceCream],
+            award,
+        };
+        let customers = vec![customer(100), customer(3), customer(100), customer(100)];
+        let mut referee = get_referee(customers);
+        referee.turns = 5;
+        let mut agents: Vec<Box<dyn Agent>> = vec![Box::new(IdleAgent), Box::new(IdleAgent)];
+        referee.play_round(0, &mut agents);
+        // the customer with 3 points left after 3 turns, the fourth customer waited 2 turns
+        let awards = referee
+            .round
+            .queue
+            .iter()
+            .map(|customer| customer.award)
+            .collect::<Vec<_>>();
+        assert_eq!(awards, vec![95, 95, 98]);
+    }
+
+    #[test]
+    fn a_matching_dish_scores_the_award_at_the_window() {
+        let customers = vec![
+            Customer {
+                order: vec![Dish, IceCream, Blueberries],
+                award: 400,
+            },
+            Customer {
+                order: vec![Dish, IceCream],
+                award: 200,
+            },
+        ];
+        let mut referee = get_referee(customers);
+        let window = [4, 6];
+        referee.round.chefs[0].pos = [4, 5];
+        referee.round.chefs[0].hand = vec![Dish, Blueberries];
+        referee.use_cell(0, window);
+        assert_eq!(referee.round.score, 0);
+        // whatever the order of the desserts
+        referee.round.chefs[0].hand = vec![Dish, Blueberries, IceCream];
+        referee.use_cell(0, window);
+        assert_eq!(referee.round.score, 400);
+        assert!(referee.round.chefs[0].hand.is_empty());
+        assert_eq!(referee.round.queue.len(), 1);
+    }
+}
@@ -1,6 +1,11 @@
 // https://www.codingame.com/multiplayer/bot-programming/code-a-la-mode
 
-use std::{collections::HashMap, io};
+use codinggame_backtocode_rust::params;
+use codinggame_backtocode_rust::timer::{self, TurnTimer};
+use codinggame_backtocode_rust::{debug, info, trace, transcript, warn, write_answer};
+use std::rc::Rc;
+use std::time::Duration;
+use std::{collections::HashMap, fmt};
 
 macro_rules! parse_input {
     ($x:expr, $t:ident) => {
@@ -8,6 +13,19 @@ macro_rules! parse_input {
     };
 }
 
+/// a chef moves up to this number of cells per turn
+const MAX_MOVES: usize = 4;
+/// turns for the oven to bake dough
+const BAKING_TURNS: i32 = 10;
+/// turns a baked item stays in the oven before burning
+const BURNING_TURNS: i32 = 10;
+/// spare turns to be back at the oven before our item burns, the partner may be in the way
+const OVEN_MARGIN: i32 = 2;
+/// customers coming after the waiting ones we prepare items for
+const LOOKAHEAD_CUSTOMERS: usize = 3;
+/// time kept to finish the turn once a search stops
+const TIME_MARGIN: Duration = Duration::from_millis(10);
+
 #[derive(Copy, Clone, Eq, Hash, PartialEq, Debug)]
 struct Point {
     x: i32,
@@ -19,20 +37,17 @@ impl Point {
         Self { x, y }
     }
 
-    pub fn get_squared_distance(&self, pos: &Point) -> i32 {
-        (pos.y - self.y).pow(2) + (pos.x - self.x).pow(2)
-    }
-
-    pub fn get_distance(&self, pos: &Point) -> f32 {
-        (self.get_squared_distance(pos) as f32).sqrt()
-    }
-
     pub fn manhattan_distance(&self, pos: &Point) -> i32 {
         let cost = 1;
         let dx = (self.x - pos.x).abs();
         let dy = (self.y - pos.y).abs();
         cost * (dx + dy)
     }
+
+    /// a chef can use the 8 cells around it
+    pub fn is_adjacent(&self, pos: &Point) -> bool {
+        self != pos && (self.x - pos.x).abs() <= 1 && (self.y - pos.y).abs() <= 1
+    }
 }
 
 #[derive(Copy, Clone, PartialEq)]
@@ -101,10 +116,6 @@ impl Cell {
         }
     }
 
-    pub fn reset(&mut self) {
-        self.cell_type = CellType::Empty;
-    }
-
     pub fn is_visitable(&self) -> bool {
         self.cell_type == CellType::Empty
     }
@@ -146,7 +157,7 @@ impl Grid {
         Self {
             height: h,
             width: w,
-            cells: cells,
+            cells,
         }
     }
 
@@ -161,12 +172,22 @@ impl Grid {
         (self.width * pos.y + pos.x) as usize
     }
 
+    pub fn is_in_bounds(&self, pos: &Point) -> bool {
+        (0..self.width).contains(&pos.x) && (0..self.height).contains(&pos.y)
+    }
+
     pub fn get_mut_cell(&mut self, pos: &Point) -> Option<&mut Cell> {
+        if !self.is_in_bounds(pos) {
+            return None;
+        }
         let index = self.get_cell_index(pos);
         self.cells.get_mut(index)
     }
 
     pub fn get_cell(&self, pos: &Point) -> Option<&Cell> {
+        if !self.is_in_bounds(pos) {
+            return None;
+        }
         let index = self.get_cell_index(pos);
         self.cells.get(index)
     }
@@ -180,11 +201,22 @@ impl Grid {
         i32::MAX
     }
 
-    pub fn reset(&mut self) {
-        self.cells.clear();
+    /// get the 4 directions neighbors a chef can move to, less than 4 points if cells are not visitables or do no exists
+    pub fn get_moves_points(&self, pos: &Point) -> Vec<Point> {
+        [
+            Point::new(pos.x - 1, pos.y),
+            Point::new(pos.x + 1, pos.y),
+            Point::new(pos.x, pos.y - 1),
+            Point::new(pos.x, pos.y + 1),
+        ]
+        .iter()
+        .filter_map(|point| self.get_cell(point))
+        .filter(|cell| cell.is_visitable())
+        .map(|cell| cell.pos)
+        .collect()
     }
 
-    /// get the 8 directions neighbors, this function can return less than 4 points if cells are not visitables or do no exists
+    /// get the 8 directions neighbors (the cells a chef can use), this function can return less than 8 points if cells are not visitables or do no exists
     pub fn get_neighbors_points(&self, pos: &Point) -> Vec<Point> {
         let mut neighbors = Vec::new();
         let points = [
@@ -207,6 +239,30 @@ impl Grid {
         neighbors
     }
 
+    /// for every cell, the number of cells a chef can walk to from it (itself included), 0 where a chef can not stand
+    pub fn get_reachable_counts(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.cells.len()];
+        for cell in &self.cells {
+            if !cell.is_visitable() || counts[self.get_cell_index(&cell.pos)] > 0 {
+                continue;
+            }
+            let mut area = vec![cell.pos];
+            let mut index = 0;
+            while index < area.len() {
+                for point in self.get_moves_points(&area[index]) {
+                    if !area.contains(&point) {
+                        area.push(point);
+                    }
+                }
+                index += 1;
+            }
+            for point in &area {
+                counts[self.get_cell_index(point)] = area.len();
+            }
+        }
+        counts
+    }
+
     /// returns the points to follow using a* in a grid where we can go over 4 directions
     ///
     /// documentation: http://theory.stanford.edu/~amitp/GameProgramming/ImplementationNotes.html
@@ -222,24 +278,24 @@ impl Grid {
             f: 0,
         });
 
-        while open.len() > 0 {
-            open.sort_by(|a, b| b.f.cmp(&a.f));
+        while !open.is_empty() {
+            open.sort_by_key(|p| std::cmp::Reverse(p.f));
             let q = open.pop().unwrap();
             closed.push(q);
 
             if debug {
-                eprintln!(
+                trace!(target: "astar",
                     "open: {:?}",
                     open.iter().map(|c| c.point).collect::<Vec<_>>()
                 );
             }
 
-            for neighbor_point in self.get_neighbors_points(&q.point) {
+            for neighbor_point in self.get_moves_points(&q.point) {
                 let mut astar_neighbor = AStarPoint {
                     g: 0,
                     h: 0,
                     f: 0,
-                    parent: Some(q.point.clone()),
+                    parent: Some(q.point),
                     point: neighbor_point,
                 };
 
@@ -257,7 +313,7 @@ impl Grid {
                                     parent_point, closed,
                                 )
                             });
-                        astar_point = closed.get(position).unwrap().clone();
+                        astar_point = *closed.get(position).unwrap();
                         path.push(astar_point.point);
                     }
                     if let Some(last) = path.pop() {
@@ -266,7 +322,9 @@ impl Grid {
                         }
                     }
                     path.reverse();
-                    eprintln!("a* path: {:?}", path);
+                    if debug {
+                        trace!(target: "astar", "a* path: {:?}", path);
+                    }
                     return path;
                 }
 
@@ -293,7 +351,7 @@ impl Grid {
             }
         }
 
-        return Vec::new();
+        Vec::new()
     }
 }
 
@@ -323,19 +381,41 @@ enum ItemType {
     ChoppedStrawberries,
     Croissant,
     Dough,
+    ChoppedDough,
+    RawTart,
+    Tart,
+}
+
+/// where an item is made
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Station {
+    Chopper,
+    Oven,
+    /// using a crate while holding the ingredient adds the crate item to it
+    Crate(ItemType),
+}
+
+/// an item is made by using the station while holding the ingredient
+#[derive(Copy, Clone, Debug)]
+struct Recipe {
+    ingredient: ItemType,
+    station: Station,
 }
 
 impl ItemType {
-    fn from_str(s: &str) -> Self {
+    fn from_str(s: &str) -> Option<Self> {
         match s {
-            "DISH" => return ItemType::Dish,
-            "BLUEBERRIES" => return ItemType::Blueberries,
-            "ICE_CREAM" => return ItemType::IceCream,
-            "STRAWBERRIES" => return ItemType::Strawberries,
-            "CHOPPED_STRAWBERRIES" => return ItemType::ChoppedStrawberries,
-            "CROISSANT" => return ItemType::Croissant,
-            "DOUGH" => return ItemType::Dough,
-            _ => panic!("unknown item type: {}", s),
+            "DISH" => Some(ItemType::Dish),
+            "BLUEBERRIES" => Some(ItemType::Blueberries),
+            "ICE_CREAM" => Some(ItemType::IceCream),
+            "STRAWBERRIES" => Some(ItemType::Strawberries),
+            "CHOPPED_STRAWBERRIES" => Some(ItemType::ChoppedStrawberries),
+            "CROISSANT" => Some(ItemType::Croissant),
+            "DOUGH" => Some(ItemType::Dough),
+            "CHOPPED_DOUGH" => Some(ItemType::ChoppedDough),
+            "RAW_TART" => Some(ItemType::RawTart),
+            "TART" => Some(ItemType::Tart),
+            _ => None,
         }
     }
 
@@ -343,26 +423,77 @@ impl ItemType {
         if str == "NONE" {
             return Vec::new();
         }
-        str.split("-").map(|s| ItemType::from_str(s)).collect()
+        str.split("-")
+            .filter_map(|s| {
+                let item = ItemType::from_str(s);
+                if item.is_none() {
+                    warn!("unknown item type: {}", s);
+                }
+                item
+            })
+            .collect()
     }
 
-    fn diff(a: Vec<Self>, b: Vec<Self>) -> Vec<Self> {
-        let mut diff = Vec::new();
-        for item_a in &a {
-            if !b.contains(item_a) {
-                diff.push(item_a.clone())
-            }
+    /// the recipe graph, `None` for items taken from a crate
+    fn get_recipe(&self) -> Option<Recipe> {
+        let (ingredient, station) = match self {
+            ItemType::ChoppedStrawberries => (ItemType::Strawberries, Station::Chopper),
+            ItemType::Croissant => (ItemType::Dough, Station::Oven),
+            ItemType::ChoppedDough => (ItemType::Dough, Station::Chopper),
+            ItemType::RawTart => (
+                ItemType::ChoppedDough,
+                Station::Crate(ItemType::Blueberries),
+            ),
+            ItemType::Tart => (ItemType::RawTart, Station::Oven),
+            _ => return None,
+        };
+        Some(Recipe {
+            ingredient,
+            station,
+        })
+    }
+
+    /// the item made by using the crate while holding the ingredient
+    fn get_crate_recipe(ingredient: ItemType, crate_item: ItemType) -> Option<ItemType> {
+        [ItemType::RawTart].into_iter().find(|product| {
+            product.get_recipe().is_some_and(|recipe| {
+                recipe.ingredient == ingredient && recipe.station == Station::Crate(crate_item)
+            })
+        })
+    }
+
+    /// making the item needs the oven at some point
+    fn uses_oven(&self) -> bool {
+        self.get_chain().iter().any(|item| {
+            item.get_recipe()
+                .is_some_and(|recipe| recipe.station == Station::Oven)
+        })
+    }
+
+    /// the item and every intermediate product needed to make it, down to the crate item
+    fn get_chain(&self) -> Vec<Self> {
+        let mut chain = vec![*self];
+        while let Some(recipe) = chain.last().and_then(|item| item.get_recipe()) {
+            chain.push(recipe.ingredient);
         }
-        for item_b in &b {
-            if !a.contains(item_b) {
-                diff.push(item_b.clone())
-            }
+        chain
+    }
+
+    /// the order we get the items of a command in, the dish first
+    fn get_task_priority(&self) -> i32 {
+        match self {
+            ItemType::Dish => 0,
+            ItemType::IceCream => 1,
+            ItemType::Blueberries => 2,
+            ItemType::ChoppedStrawberries => 3,
+            ItemType::Croissant => 4,
+            ItemType::Tart => 5,
+            _ => 6,
         }
-        diff
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 struct Oven {
     content: Option<ItemType>,
     timer: i32,
@@ -378,13 +509,32 @@ impl Oven {
         }
     }
 
+    /// the item the oven gives once baked, whether it is baked yet or not
+    fn get_product(&self) -> Option<ItemType> {
+        match self.content? {
+            ItemType::Dough => Some(ItemType::Croissant),
+            ItemType::RawTart => Some(ItemType::Tart),
+            content => Some(content),
+        }
+    }
+
+    fn is_baked(&self) -> bool {
+        matches!(self.content, Some(ItemType::Croissant | ItemType::Tart))
+    }
+
+    /// turns left to take the product before it burns
+    fn get_burn_turns(&self) -> i32 {
+        if self.is_baked() {
+            self.timer
+        } else {
+            self.timer + BURNING_TURNS
+        }
+    }
+
     fn update_from_raw(&mut self, str: String) {
         let inputs = str.split(" ").collect::<Vec<_>>();
-        let oven_contents = inputs[0].trim().to_string(); // ignore until wood 1 league
-        self.content = None;
-        if oven_contents != "NONE" {
-            self.content = Some(ItemType::from_str(&oven_contents));
-        }
+        let oven_contents = inputs[0].trim().to_string();
+        self.content = ItemType::from_str(&oven_contents);
         self.timer = parse_input!(inputs[1], i32);
     }
 }
@@ -405,9 +555,26 @@ impl Command {
 
         Self { id, award, order }
     }
+
+    /// items of the order missing from the hand, in the order we get them
+    fn get_tasks(&self, hand: &[ItemType]) -> Vec<ItemType> {
+        let mut tasks = self
+            .order
+            .iter()
+            .filter(|item| !hand.contains(item))
+            .copied()
+            .collect::<Vec<_>>();
+        tasks.sort_by_key(|item| item.get_task_priority());
+        tasks
+    }
+
+    /// every item of the hand is part of the order
+    fn fits(&self, hand: &[ItemType]) -> bool {
+        hand.iter().all(|item| self.order.contains(item))
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 struct Table {
     items: Vec<ItemType>,
     pos: Point,
@@ -419,53 +586,259 @@ impl Table {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Action {
+    Move(Point),
+    Use(Point),
+    Wait,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Move(pos) => write!(f, "MOVE {} {}", pos.x, pos.y),
+            Action::Use(pos) => write!(f, "USE {} {}", pos.x, pos.y),
+            Action::Wait => write!(f, "WAIT"),
+        }
+    }
+}
+
+/// what the chef is doing, kept until it is done or not valid anymore
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Task {
+    /// take the item with empty hands (a table, a crate, the oven), the dishwasher also empties our dish
+    FetchItem(ItemType),
+    /// take the dish left on this table with some items of our order
+    TakeDish(Point),
+    /// chop the ingredient of the item
+    Chop(ItemType),
+    /// put the ingredient of the item in the oven
+    Bake(ItemType),
+    /// stay next to the oven until the item is baked, then take it
+    WaitOven(ItemType),
+    /// put our hand on a free table
+    Drop,
+    /// add the item to what we hold: a dish, or the ingredient of a crate recipe
+    Assemble(ItemType),
+    /// give our dish at the window
+    Deliver,
+}
+
+impl Task {
+    /// what we should hold (and the kitchen should look like) to work on the task
+    fn is_valid(&self, game: &Game) -> bool {
+        let hand = &game.player_hand;
+        match *self {
+            Task::FetchItem(ItemType::Dish) => hand.is_empty() || hand.contains(&ItemType::Dish),
+            Task::FetchItem(item) => hand.is_empty() && game.find_item(item).is_some(),
+            Task::TakeDish(pos) => {
+                hand.is_empty()
+                    && game.player_command.as_ref().is_some_and(|command| {
+                        game.tables.iter().any(|table| {
+                            table.pos == pos
+                                && table.items.contains(&ItemType::Dish)
+                                && command.fits(&table.items)
+                        })
+                    })
+            }
+            Task::Chop(item) | Task::Bake(item) => {
+                let station = if let Task::Chop(_) = self {
+                    Station::Chopper
+                } else {
+                    Station::Oven
+                };
+                item.get_recipe()
+                    .is_some_and(|recipe| recipe.station == station && *hand == [recipe.ingredient])
+            }
+            Task::WaitOven(item) => {
+                game.is_being_baked(item)
+                    || game
+                        .oven
+                        .as_ref()
+                        .is_some_and(|oven| oven.content == Some(item))
+            }
+            Task::Drop => !hand.is_empty() && game.find_drop_table(None).is_some(),
+            Task::Assemble(item) => {
+                let holds_dish = hand.contains(&ItemType::Dish) && !hand.contains(&item);
+                let holds_ingredient =
+                    hand.len() == 1 && ItemType::get_crate_recipe(hand[0], item).is_some();
+                (holds_dish || holds_ingredient) && game.is_available(item)
+            }
+            Task::Deliver => game
+                .commands
+                .iter()
+                .any(|command| command.fits(hand) && command.get_tasks(hand).is_empty()),
+        }
+    }
+
+    /// the hand (or the kitchen) shows the task is over
+    fn is_done(&self, game: &Game) -> bool {
+        let hand = &game.player_hand;
+        match *self {
+            Task::FetchItem(ItemType::Dish) => *hand == [ItemType::Dish],
+            Task::FetchItem(item) | Task::WaitOven(item) => hand.contains(&item),
+            Task::TakeDish(_) => hand.contains(&ItemType::Dish),
+            Task::Chop(item) => *hand == [item],
+            Task::Bake(item) => hand.is_empty() && game.is_available(item),
+            Task::Drop | Task::Deliver => hand.is_empty(),
+            Task::Assemble(item) => {
+                hand.contains(&item)
+                    || hand.iter().any(|held| {
+                        held.get_recipe()
+                            .is_some_and(|recipe| recipe.station == Station::Crate(item))
+                    })
+            }
+        }
+    }
+}
+
+/// the tunable values, the constants unless overridden (see `src/params.rs`)
+#[derive(Clone)]
+struct Params {
+    oven_margin: i32,
+    lookahead_customers: usize,
+}
+
+impl Params {
+    pub fn load() -> Self {
+        Self {
+            oven_margin: params::get("oven_margin", OVEN_MARGIN),
+            lookahead_customers: params::get("lookahead_customers", LOOKAHEAD_CUSTOMERS),
+        }
+    }
+}
+
+/// how the walls of the kitchen split it, they do not move during the game so it is computed once
+#[derive(Default)]
+struct KitchenPaths {
+    /// by cell: at most 2 ways out, two chefs can not cross there
+    chokepoints: Vec<bool>,
+    /// by cell: the number of cells a chef can walk to from it
+    reachable: Vec<usize>,
+    /// by blocked cell then by cell: the number of cells a chef can walk to from it once the blocked cell is a wall
+    reachable_around: Vec<Vec<usize>>,
+}
+
+impl KitchenPaths {
+    /// from the grid without the chefs
+    fn new(grid: &Grid) -> Self {
+        let reachable = grid.get_reachable_counts();
+        let reachable_around = grid
+            .cells
+            .iter()
+            .map(|cell| {
+                if !cell.is_visitable() {
+                    return reachable.clone();
+                }
+                let mut grid = grid.clone();
+                grid.set_cell_type(&cell.pos, CellType::Wall);
+                let mut counts = grid.get_reachable_counts();
+                counts[grid.get_cell_index(&cell.pos)] = reachable[grid.get_cell_index(&cell.pos)];
+                counts
+            })
+            .collect();
+        Self {
+            chokepoints: grid
+                .cells
+                .iter()
+                .map(|cell| grid.get_moves_points(&cell.pos).len() <= 2)
+                .collect(),
+            reachable,
+            reachable_around,
+        }
+    }
+}
+
+#[derive(Clone)]
 struct Game {
     grid: Grid,
     windows: Vec<Point>,
     choppers: Vec<Point>,
     oven: Option<Oven>,
     crates: HashMap<ItemType, Point>,
+    /// set once the kitchen is read, see `set_kitchen_paths`
+    paths: Rc<KitchenPaths>,
 
+    /// every customer of the round, in the order they come
+    customers: Vec<Command>,
+    /// customers that came in so far (waiting or served)
+    customers_seen: usize,
     commands: Vec<Command>,
     tables: Vec<Table>,
     partner_pos: Option<Point>,
+    /// where the partner was before their last move, to guess where they go
+    partner_last_pos: Option<Point>,
+    /// our turns since the partner last moved, chefs play in turns so they may have moved twice or not at all
+    partner_still_turns: i32,
     partner_hand: Vec<ItemType>,
 
     player_pos: Point,
     player_hand: Vec<ItemType>,
-    player_command_id: Option<i32>,
-    player_chopping_item: Option<ItemType>,
-    player_dropping_choped_item: bool,
-    player_baking_item: Option<ItemType>,
-    player_dropping_backed_item: bool,
+    player_command: Option<Command>,
+    player_task: Option<Task>,
+    /// the item we put in the oven, we have to take it before it burns
+    player_baking: Option<ItemType>,
+    /// the time left this turn
+    timer: TurnTimer,
+    params: Params,
 }
 
 impl Game {
     pub fn new(w: i32, h: i32) -> Self {
         Self {
             grid: Grid::new(w, h),
+            customers: Vec::new(),
+            customers_seen: 0,
             commands: Vec::new(),
             player_pos: Point::new(0, 0),
             player_hand: Vec::new(),
             partner_pos: None,
+            partner_last_pos: None,
+            partner_still_turns: 0,
             partner_hand: Vec::new(),
             tables: Vec::new(),
-            player_command_id: None,
+            player_command: None,
             windows: Vec::new(),
             crates: HashMap::new(),
             choppers: Vec::new(),
             oven: None,
-            player_chopping_item: None,
-            player_dropping_choped_item: false,
-            player_baking_item: None,
-            player_dropping_backed_item: false,
+            paths: Rc::default(),
+            player_task: None,
+            player_baking: None,
+            timer: TurnTimer::unlimited(),
+            params: Params::load(),
         }
     }
 
     pub fn is_oven_used(&self) -> bool {
         self.oven
             .as_ref()
-            .map_or(false, |oven| oven.content.is_some())
+            .is_some_and(|oven| oven.content.is_some())
+    }
+
+    /// the oven is baking the ingredient of this item
+    pub fn is_being_baked(&self, item: ItemType) -> bool {
+        let Some(recipe) = item.get_recipe() else {
+            return false;
+        };
+        recipe.station == Station::Oven
+            && self
+                .oven
+                .as_ref()
+                .is_some_and(|oven| oven.content == Some(recipe.ingredient))
+    }
+
+    /// we can get the item now (table, crate, oven) or once the oven is done
+    pub fn is_available(&self, item: ItemType) -> bool {
+        self.find_item(item).is_some() || self.is_being_baked(item)
+    }
+
+    pub fn get_station_pos(&self, station: Station) -> Option<Point> {
+        match station {
+            Station::Chopper => self.choppers.first().copied(),
+            Station::Oven => self.oven.as_ref().map(|oven| oven.pos),
+            Station::Crate(item) => self.crates.get(&item).copied(),
+        }
     }
 
     pub fn add_table(&mut self, table: Table) {
@@ -489,7 +862,13 @@ impl Game {
         if let Some(old_pos) = self.partner_pos {
             self.grid.set_cell_type(&old_pos, CellType::Empty);
         }
-        self.partner_pos = Some(partner_pos.clone());
+        if self.partner_pos == Some(partner_pos) {
+            self.partner_still_turns += 1;
+        } else {
+            self.partner_last_pos = self.partner_pos;
+            self.partner_still_turns = 0;
+        }
+        self.partner_pos = Some(partner_pos);
         self.grid.set_cell_type(&partner_pos, CellType::Partner);
     }
 
@@ -501,270 +880,825 @@ impl Game {
         for (x, c) in row.chars().enumerate() {
             let cell_type = CellType::from_char(c);
             let pos = &Point::new(x as i32, y);
-            self.grid.set_cell_type(pos, cell_type.clone());
+            self.grid.set_cell_type(pos, cell_type);
 
             match cell_type {
                 CellType::Window => {
-                    self.windows.push(pos.clone());
+                    self.windows.push(*pos);
                 }
                 CellType::Blueberries => {
-                    self.crates.insert(ItemType::Blueberries, pos.clone());
+                    self.crates.insert(ItemType::Blueberries, *pos);
                 }
                 CellType::IceCream => {
-                    self.crates.insert(ItemType::IceCream, pos.clone());
+                    self.crates.insert(ItemType::IceCream, *pos);
                 }
                 CellType::DishWasher => {
-                    self.crates.insert(ItemType::Dish, pos.clone());
+                    self.crates.insert(ItemType::Dish, *pos);
                 }
                 CellType::Strawberries => {
-                    self.crates.insert(ItemType::Strawberries, pos.clone());
+                    self.crates.insert(ItemType::Strawberries, *pos);
                 }
                 CellType::Dough => {
-                    self.crates.insert(ItemType::Dough, pos.clone());
+                    self.crates.insert(ItemType::Dough, *pos);
                 }
                 CellType::Chopper => {
-                    self.choppers.push(pos.clone());
+                    self.choppers.push(*pos);
                 }
                 CellType::Oven => {
-                    self.oven = Some(Oven::new(pos.clone()));
+                    self.oven = Some(Oven::new(*pos));
                 }
                 _ => {}
             }
         }
     }
 
+    /// once every row is decoded, before a chef stands in the kitchen
+    pub fn set_kitchen_paths(&mut self) {
+        self.paths = Rc::new(KitchenPaths::new(&self.grid));
+    }
+
     pub fn add_command(&mut self, command: Command) {
         self.commands.push(command);
     }
 
-    pub fn find_empty_space(&self) -> Option<Point> {
-        for cell in &self.grid.cells {
-            if cell.cell_type == CellType::Wall {
-                let mut is_table = false;
-                for table in &self.tables {
-                    if table.pos == cell.pos {
-                        is_table = true;
-                    }
+    pub fn add_customer(&mut self, customer: Command) {
+        self.customers.push(customer);
+    }
+
+    /// finds the waiting commands in the customers list: the ones after the last of them did not come yet
+    pub fn update_customers_seen(&mut self) {
+        let mut seen = 0;
+        for command in &self.commands {
+            if let Some(index) = self.customers[seen..]
+                .iter()
+                .position(|customer| customer.order == command.order)
+            {
+                seen += index + 1;
+            }
+        }
+        self.customers_seen = seen;
+    }
+
+    /// the prepared item (without the oven) the next customers need the most and that is not ready on a table
+    pub fn get_lookahead_product(&self) -> Option<ItemType> {
+        let end = self
+            .customers
+            .len()
+            .min(self.customers_seen + self.params.lookahead_customers);
+        let upcoming = self.customers[self.customers_seen.min(end)..end]
+            .iter()
+            .chain(self.commands.iter())
+            .filter(|command| {
+                !self
+                    .player_command
+                    .as_ref()
+                    .is_some_and(|picked| picked.id == command.id && picked.order == command.order)
+            });
+        let mut needs = HashMap::<ItemType, i32>::new();
+        for command in upcoming {
+            for item in &command.order {
+                if item.get_recipe().is_some() && !item.uses_oven() {
+                    *needs.entry(*item).or_default() += 1;
                 }
-                if !is_table {
-                    return Some(cell.pos);
+            }
+        }
+        for table in &self.tables {
+            if let [item] = table.items[..] {
+                if let Some(need) = needs.get_mut(&item) {
+                    *need -= 1;
                 }
             }
         }
-        return None;
+        needs
+            .into_iter()
+            .filter(|(_, need)| *need > 0)
+            .max_by_key(|(item, need)| (*need, -item.get_task_priority()))
+            .map(|(item, _)| item)
     }
 
-    pub fn drop_choped_item(&mut self) -> String {
-        eprintln!("-> choped! need to find a table to drop it now");
-        let pos = self
-            .find_empty_space()
-            .expect("no empty space found to drop hand");
-        self.player_dropping_choped_item = true;
-        return format!("USE {} {}", pos.x, pos.y);
+    /// turns to make the item from the crate through each station of its recipe then drop it, and where we end
+    pub fn estimate_prepare_turns(&self, product: ItemType) -> Option<(i32, Point)> {
+        let mut stations = Vec::new();
+        let mut current = product;
+        while let Some(recipe) = current.get_recipe() {
+            stations.push(self.get_station_pos(recipe.station)?);
+            current = recipe.ingredient;
+        }
+        stations.push(*self.crates.get(&current)?);
+
+        let mut turns = 1;
+        let mut position = self.player_pos;
+        for station in stations.iter().rev() {
+            let (use_turns, end) = self.get_use_turns(&position, station)?;
+            turns += use_turns;
+            position = end;
+        }
+        Some((turns, position))
     }
 
-    pub fn drop_baked_item(&mut self) -> String {
-        eprintln!("-> baked! need to find a table to drop it now");
-        let pos = self
-            .find_empty_space()
-            .expect("no empty space found to drop hand");
-        self.player_dropping_backed_item = true;
-        return format!("USE {} {}", pos.x, pos.y);
+    /// our dish only misses what the oven bakes, in our hand or left on a table
+    pub fn is_waiting_oven(&self, command: &Command) -> bool {
+        let dish = if self.player_hand.is_empty() {
+            self.find_partial_dish(command).map(|table| &table.items)
+        } else {
+            Some(&self.player_hand)
+        };
+        let Some(dish) = dish.filter(|dish| dish.contains(&ItemType::Dish) && command.fits(dish))
+        else {
+            return false;
+        };
+        let tasks = command.get_tasks(dish);
+        !tasks.is_empty() && tasks.iter().all(|item| self.is_being_baked(*item))
     }
 
-    pub fn find_command_without_baking(&self) -> Option<Command> {
-        for command in &self.commands {
-            if command.order.contains(&ItemType::Croissant) {
-                continue;
-            }
-            return Some(command.clone());
+    /// while the oven bakes the last item of our dish, prepare an item for the next customers (putting our dish
+    /// aside first) if we can be back at the oven once it is baked
+    pub fn get_lookahead_task(&self, command: &Command) -> Option<Task> {
+        let oven = self.oven.as_ref()?;
+        if !self.is_waiting_oven(command) {
+            return None;
+        }
+        let product = self.get_lookahead_product()?;
+        let (turns, end) = self.estimate_prepare_turns(product)?;
+        let (back_turns, _) = self.get_use_turns(&end, &oven.pos)?;
+        // dropping our dish then taking it back
+        let dish_turns = if self.player_hand.is_empty() { 0 } else { 2 };
+        if turns + back_turns + dish_turns > oven.timer + self.params.oven_margin {
+            return None;
+        }
+        if self.player_hand.is_empty() {
+            Some(self.get_recipe_task(product))
+        } else {
+            Some(Task::Drop)
         }
-        None
     }
 
-    pub fn step(&mut self) -> String {
-        // find a command
-        if self.player_command_id.is_none() {
-            // TODO: not started by partner
-            eprintln!("-> no command picked yet, taking one");
-            self.player_command_id = Some(0);
+    /// the free table where dropping our hand costs the fewest turns on the way to the next target, tables we use
+    /// from a corridor come last as we would block the partner while standing there
+    pub fn find_drop_table(&self, next: Option<Point>) -> Option<Point> {
+        self.grid
+            .cells
+            .iter()
+            .filter(|cell| cell.cell_type == CellType::Wall)
+            .filter(|cell| !self.tables.iter().any(|table| table.pos == cell.pos))
+            .filter_map(|cell| {
+                let (use_turns, end) = self.get_use_turns(&self.player_pos, &cell.pos)?;
+                // the turns to be next to the target, using it is counted by its task
+                let next_turns = match next {
+                    Some(next) => self.get_use_turns(&end, &next)?.0 - 1,
+                    None => 0,
+                };
+                let blocking = i32::from(self.is_chokepoint(&end));
+                Some((use_turns + next_turns + blocking, cell.pos))
+            })
+            .min_by_key(|(cost, _)| *cost)
+            .map(|(_, pos)| pos)
+    }
+
+    /// where we go once our hand is dropped, to drop it on the way
+    pub fn get_target_after_drop(&self) -> Option<Point> {
+        let command = self.player_command.as_ref()?;
+        let mut game = self.clone();
+        game.player_hand.clear();
+        let task = game
+            .get_oven_task(command)
+            .unwrap_or_else(|| game.get_next_task(command));
+        match game.get_task_action(task) {
+            Action::Use(pos) | Action::Move(pos) => Some(pos),
+            Action::Wait => None,
         }
+    }
 
-        let player_command_id = self.player_command_id.expect("player do  not have command");
-        let command = self
-            .commands
-            .get(player_command_id as usize)
-            .unwrap_or_else(|| panic!("command id not found in vec: {}", player_command_id));
-
-        eprintln!("player_hand: {:?}", self.player_hand);
-        eprintln!("command_order: {:?}", command.order);
-
-        // we finished baking, find a table
-        if self.player_dropping_backed_item {
-            if self.player_hand.len() == 0 {
-                eprintln!("-> ok we have droped baked item - let resume");
-                self.player_baking_item = None;
-                self.player_dropping_backed_item = false;
-            } else {
-                return self.drop_baked_item();
-            }
-        }
-
-        // if we are starting to bake
-        if let Some(player_baking_item) = self.player_baking_item {
-            if !self.is_oven_used() {
-                eprintln!("-> need to bake item: {:?}", player_baking_item);
-                if self.player_hand.len() > 1 || self.player_hand.contains(&ItemType::Dish) {
-                    eprintln!("-> hands are full - need to drop hand somewhere");
-                    let pos = self
-                        .find_empty_space()
-                        .expect("no empty space found to drop hand");
-                    return format!("USE {} {}", pos.x, pos.y);
-                }
-                if self.player_hand.contains(&ItemType::Dough) {
-                    eprintln!(
-                        "-> already having item to bake - baking it: {:?}",
-                        ItemType::Dough
-                    );
-                    let oven = &self.oven.as_ref().expect("no oven found!").pos;
-                    return format!("USE {} {}", oven.x, oven.y);
+    /// the dish on a table with the most items of the order and nothing else, the partner (or us) may have left it
+    pub fn find_partial_dish(&self, command: &Command) -> Option<&Table> {
+        self.tables
+            .iter()
+            .filter(|table| {
+                table.items.contains(&ItemType::Dish)
+                    && table.items.len() > 1
+                    && command.fits(&table.items)
+            })
+            .filter_map(|table| {
+                let (turns, _) = self.get_use_turns(&self.player_pos, &table.pos)?;
+                Some((table, turns))
+            })
+            .min_by_key(|(table, turns)| (-(table.items.len() as i32), *turns))
+            .map(|(table, _)| table)
+    }
+
+    /// the shortest path to a floor cell next to the target, empty if we already are next to it
+    /// `None` if the target can not be reached (the partner may block the way)
+    pub fn get_use_path(&self, from: &Point, target: &Point) -> Option<Vec<Point>> {
+        if from.is_adjacent(target) {
+            return Some(Vec::new());
+        }
+        self.grid
+            .get_neighbors_points(target)
+            .iter()
+            .map(|point| self.grid.astar(from, point, false))
+            .filter(|path| !path.is_empty())
+            .min_by_key(|path| path.len())
+    }
+
+    /// turns to go next to the target and use it, and the position we use it from
+    pub fn get_use_turns(&self, from: &Point, target: &Point) -> Option<(i32, Point)> {
+        let path = self.get_use_path(from, target)?;
+        let end = path.last().copied().unwrap_or(*from);
+        let moving_turns = path.len().div_ceil(MAX_MOVES) as i32;
+        Some((moving_turns + 1, end))
+    }
+
+    /// estimated turns to complete the command from our position and hand
+    /// prepared items are made following their recipe before taking the dish, the ones ending in our hand are
+    /// dropped next to their station then taken back once we have the dish
+    pub fn estimate_command_turns(&self, command: &Command) -> Option<i32> {
+        // a dish with items the customer does not want goes back to the dishwasher first
+        let mut hand = if command.fits(&self.player_hand) {
+            self.player_hand.clone()
+        } else {
+            Vec::new()
+        };
+        // a dish left on a table replaces the one from the dishwasher
+        let partial_dish = hand
+            .is_empty()
+            .then(|| self.find_partial_dish(command))
+            .flatten();
+        if let Some(table) = partial_dish {
+            hand = table.items.clone();
+        }
+        let oven = self.oven.as_ref();
+        let mut baked_at = match oven.and_then(|oven| oven.content) {
+            Some(ItemType::Croissant | ItemType::Tart) => Some(0),
+            Some(ItemType::Dough | ItemType::RawTart) => oven.map(|oven| oven.timer),
+            _ => None,
+        };
+
+        let mut preparation = Vec::new();
+        let mut assembly = partial_dish
+            .map(|table| table.pos)
+            .into_iter()
+            .collect::<Vec<_>>();
+        for item in &command.get_tasks(&hand) {
+            if let Some(Action::Use(source)) = self.find_item(*item) {
+                assembly.push(source);
+                continue;
+            }
+            if self.is_being_baked(*item) {
+                assembly.push(oven?.pos);
+                continue;
+            }
+
+            // stations of the recipe from the last one, down to an ingredient we can get
+            let mut stations = Vec::new();
+            let mut current = *item;
+            while let Some(recipe) = current.get_recipe() {
+                stations.push(self.get_station_pos(recipe.station)?);
+                current = recipe.ingredient;
+                if let Some(Action::Use(source)) = self.find_item(current) {
+                    stations.push(source);
+                    break;
                 }
-                if self.player_hand.contains(&ItemType::Croissant) {
-                    return self.drop_baked_item();
+            }
+            stations.reverse();
+            let last = *stations.last()?;
+            preparation.extend(stations);
+            if item.get_recipe()?.station != Station::Oven {
+                // drop it on a table next to the station
+                preparation.push(last);
+            }
+            assembly.push(last);
+        }
+        assembly.push(*self.windows.first()?);
+
+        let mut turns = 0;
+        let mut position = self.player_pos;
+        for target in preparation.iter().chain(assembly.iter()) {
+            let (use_turns, end) = self.get_use_turns(&position, target)?;
+            turns += use_turns;
+            position = end;
+            if oven.is_some_and(|oven| oven.pos == *target) {
+                match baked_at {
+                    None => baked_at = Some(turns + BAKING_TURNS),
+                    Some(baked_at) => turns = turns.max(baked_at + 1),
                 }
+            }
+        }
+        Some(turns)
+    }
 
-                eprintln!("-> not having item - finding one");
-                return self.find_item(player_baking_item).unwrap_or("WAIT".into());
-            } else {
-                // if we have something in hand it means we get interrupteed
-                // if this is not a plate, drop it somewhere and resume
-                // in all cases we clean the need of baking for now
-                self.player_baking_item = None;
-
-                // finding a command without baking need and resume from here
-                if let Some(command) = self.find_command_without_baking() {
-                    self.player_command_id = Some(command.id);
-                }
+    /// moves next to the target without using it, to be ready once it is needed
+    pub fn stage_near(&self, target: &Point) -> Action {
+        match self.get_use_path(&self.player_pos, target) {
+            Some(path) if !path.is_empty() => Action::Move(path[path.len().min(MAX_MOVES) - 1]),
+            _ => Action::Wait,
+        }
+    }
 
-                if self.player_hand.len() > 1 || !self.player_hand.contains(&ItemType::Dish) {
-                    eprintln!(
-                        "-> we wanted to bake but got interrupted -> dropping item to resume"
-                    );
-                    return self.drop_baked_item();
-                }
+    /// turns a `USE` of a far station into a `MOVE` following our own path around the partner
+    pub fn plan(&self, action: Action) -> Action {
+        let Action::Use(target) = action else {
+            return action;
+        };
+        match self.get_use_path(&self.player_pos, &target) {
+            Some(path) if !path.is_empty() => Action::Move(path[path.len().min(MAX_MOVES) - 1]),
+            Some(_) => action,
+            // the partner blocks the way: leave the corridor if they are coming, else let the server find a path
+            None => self.yield_to_partner().unwrap_or(action),
+        }
+    }
 
-                return self.step();
-            }
+    /// the partner moved during the last two turns
+    pub fn is_partner_moving(&self) -> bool {
+        self.partner_last_pos.is_some() && self.partner_still_turns <= 1
+    }
+
+    /// standing here, we cut the partner from most of the kitchen
+    pub fn is_partner_trapped(&self, pos: &Point) -> bool {
+        let Some(partner_pos) = self.partner_pos else {
+            return false;
+        };
+        if !self.grid.is_in_bounds(pos) {
+            return false;
+        }
+        let start = self.grid.get_cell_index(&partner_pos);
+        let free = self.paths.reachable.get(start);
+        let left = self
+            .paths
+            .reachable_around
+            .get(self.grid.get_cell_index(pos))
+            .and_then(|counts| counts.get(start));
+        matches!((free, left), (Some(free), Some(left)) if left * 2 < *free)
+    }
+
+    /// a cell with at most 2 ways out, two chefs can not cross there
+    pub fn is_chokepoint(&self, pos: &Point) -> bool {
+        self.grid.is_in_bounds(pos)
+            && self
+                .paths
+                .chokepoints
+                .get(self.grid.get_cell_index(pos))
+                .is_some_and(|chokepoint| *chokepoint)
+    }
+
+    /// when we stand in a corridor the moving partner needs (or trap them), the closest open cell to step aside in,
+    /// away from the partner, or a corridor cell that does not trap them in a narrow kitchen
+    pub fn yield_to_partner(&self) -> Option<Action> {
+        let partner_pos = self.partner_pos?;
+        let is_in_the_way = self.is_partner_moving() && self.is_chokepoint(&self.player_pos);
+        if !is_in_the_way && !self.is_partner_trapped(&self.player_pos) {
+            return None;
         }
+        let target = self
+            .grid
+            .cells
+            .iter()
+            .filter(|cell| cell.is_visitable())
+            .filter(|cell| !cell.pos.is_adjacent(&partner_pos))
+            .filter(|cell| !self.is_partner_trapped(&cell.pos))
+            .map(|cell| {
+                (
+                    cell.pos,
+                    self.grid.astar(&self.player_pos, &cell.pos, false),
+                )
+            })
+            .filter(|(_, path)| !path.is_empty() && path.len() <= MAX_MOVES)
+            .min_by_key(|(pos, path)| {
+                (
+                    self.is_chokepoint(pos),
+                    path.len(),
+                    -pos.manhattan_distance(&partner_pos),
+                )
+            })?
+            .0;
+        debug!(
+            "-> yielding the corridor to the partner, stepping to {:?}",
+            target
+        );
+        Some(Action::Move(target))
+    }
 
-        // we finished choping, finding a table
-        if self.player_dropping_choped_item {
-            if self.player_hand.len() == 0 {
-                eprintln!("-> ok we have droped choped item - let resume");
-                self.player_chopping_item = None;
-                self.player_dropping_choped_item = false;
-            } else {
-                return self.drop_choped_item();
-            }
+    /// the order the partner is most likely preparing: the one their dish fits with the fewest missing items, the
+    /// one needing the food they hold, or the one whose next items they are walking to
+    pub fn get_partner_command(&self) -> Option<&Command> {
+        let hand = &self.partner_hand;
+        if hand.contains(&ItemType::Dish) && hand.len() > 1 {
+            return self
+                .commands
+                .iter()
+                .filter(|command| command.fits(hand))
+                .min_by_key(|command| command.get_tasks(hand).len());
+        }
+        if let Some(food) = hand.first().filter(|item| **item != ItemType::Dish) {
+            return self
+                .commands
+                .iter()
+                .filter(|command| {
+                    command
+                        .order
+                        .iter()
+                        .any(|item| item.get_chain().contains(food))
+                })
+                .max_by_key(|command| command.award);
         }
 
-        // if we are starting to chop, chop
-        if let Some(player_chopping_item) = self.player_chopping_item {
-            eprintln!("-> need to chope item: {:?}", player_chopping_item);
-            if self.player_hand.len() > 1 || self.player_hand.contains(&ItemType::Dish) {
-                eprintln!("-> hands are full - need to drop hand somewhere");
-                let pos = self
-                    .find_empty_space()
-                    .expect("no empty space found to drop hand");
-                return format!("USE {} {}", pos.x, pos.y);
+        // the sources of the missing items the partner walked closer to, only if a single order needs them
+        let (Some(last_pos), Some(pos)) = (self.partner_last_pos, self.partner_pos) else {
+            return None;
+        };
+        let approach = |command: &Command| {
+            command
+                .get_tasks(hand)
+                .iter()
+                .filter(|item| **item != ItemType::Dish)
+                .filter_map(|item| self.get_station_pos(Station::Crate(*item)))
+                .map(|source| {
+                    source.manhattan_distance(&last_pos) - source.manhattan_distance(&pos)
+                })
+                .max()
+                .unwrap_or(0)
+        };
+        let approaches = self
+            .commands
+            .iter()
+            .map(|command| (approach(command), command))
+            .filter(|(approach, _)| *approach > 0)
+            .collect::<Vec<_>>();
+        let best = approaches.iter().map(|(approach, _)| *approach).max()?;
+        let mut closest = approaches.iter().filter(|(approach, _)| *approach == best);
+        match (closest.next(), closest.next()) {
+            (Some((_, command)), None) => Some(command),
+            _ => None,
+        }
+    }
+
+    /// the partner holds a dish with some items of this order, or we guess it is the one they prepare
+    pub fn is_prepared_by_partner(&self, command: &Command) -> bool {
+        let holds_dish = self.partner_hand.contains(&ItemType::Dish)
+            && self.partner_hand.len() > 1
+            && command.fits(&self.partner_hand);
+        holds_dish
+            || self
+                .get_partner_command()
+                .is_some_and(|partner_command| partner_command.id == command.id)
+    }
+
+    /// the command with the best award per estimated turn, among the ones the partner is not preparing
+    pub fn select_command(&self) -> Option<Command> {
+        let mut best: Option<(f64, &Command)> = None;
+        for command in &self.commands {
+            if best.is_some() && self.timer.should_stop(TIME_MARGIN) {
+                warn!("out of time, keeping the best command so far");
+                break;
             }
-            if self.player_hand.contains(&ItemType::Strawberries) {
-                eprintln!(
-                    "-> already having item to chope - chopping it: {:?}",
-                    ItemType::Strawberries
-                );
-                let choper = self.choppers.get(0).expect("no choper found!");
-                return format!("USE {} {}", choper.x, choper.y);
+            if self.is_prepared_by_partner(command) {
+                debug!("command {} is prepared by partner", command.id);
+                continue;
             }
-            if self.player_hand.contains(&ItemType::ChoppedStrawberries) {
-                return self.drop_choped_item();
+            let Some(turns) = self.estimate_command_turns(command) else {
+                continue;
+            };
+            let score = f64::from(command.award) / f64::from(turns.max(1));
+            debug!(
+                "command {} {:?}: award {} in {} turns, score {:.1}",
+                command.id, command.order, command.award, turns, score
+            );
+            if !best.is_some_and(|(best_score, _)| best_score >= score) {
+                best = Some((score, command));
             }
+        }
+        best.map(|(_, command)| command.clone())
+    }
 
-            eprintln!("-> not having item - finding one");
-            return self
-                .find_item(player_chopping_item)
-                .unwrap_or("WAIT".into());
+    /// the next task to make the item following its recipe: walks down the recipe until we hold the
+    /// ingredient or we can fetch it
+    fn get_recipe_task(&self, product: ItemType) -> Task {
+        let Some(recipe) = product.get_recipe() else {
+            return Task::FetchItem(product);
+        };
+        if self.player_hand == [recipe.ingredient] {
+            return match recipe.station {
+                Station::Chopper => Task::Chop(product),
+                Station::Oven => Task::Bake(product),
+                Station::Crate(item) => Task::Assemble(item),
+            };
+        }
+        if self.player_hand.is_empty() && self.find_item(recipe.ingredient).is_some() {
+            return Task::FetchItem(recipe.ingredient);
         }
+        self.get_recipe_task(recipe.ingredient)
+    }
 
-        // command complete, deliver it
-        let mut diff = ItemType::diff(self.player_hand.clone(), command.order.clone());
-        if diff.len() == 0 {
-            eprintln!("-> command is completed, delivering it");
-            // find window and go there
-            self.player_hand.clear();
-            return format!("USE {} {}", self.windows[0].x, self.windows[0].y);
+    /// takes the product out of the oven when we would not be back in time otherwise (dropping our hand first if
+    /// it can not take it), if we baked it or our order needs it and the partner will not take it first
+    pub fn get_oven_task(&self, command: &Command) -> Option<Task> {
+        let oven = self.oven.as_ref()?;
+        let product = oven.get_product()?;
+        let hand = &self.player_hand;
+        let is_ours = self.player_baking == Some(product);
+        if !is_ours && !command.get_tasks(hand).contains(&product) {
+            return None;
+        }
+        let (turns, _) = self.get_use_turns(&self.player_pos, &oven.pos)?;
+        if oven.get_burn_turns() > turns + self.params.oven_margin {
+            return None;
         }
 
-        // command imcomplete, complete it
-        // TODO: find closest item -and first dish-
-        eprintln!("-> command is incomplete, find missing elements starting with dish");
-        let mut missing_item = ItemType::Dish;
-        if !diff.contains(&ItemType::Dish) {
-            eprintln!("-> dish is there we need food now");
-            missing_item = diff
-                .pop()
-                .expect("Diff order is empty, but should not at this stage");
+        let can_take = |hand: &[ItemType]| {
+            hand.is_empty() || (hand.contains(&ItemType::Dish) && !hand.contains(&product))
+        };
+        let partner_turns = self
+            .partner_pos
+            .and_then(|partner_pos| self.get_use_turns(&partner_pos, &oven.pos));
+        if !is_ours
+            && can_take(&self.partner_hand)
+            && partner_turns.is_some_and(|(partner_turns, _)| partner_turns < turns)
+        {
+            return None;
         }
+        if can_take(hand) && command.fits(hand) {
+            Some(Task::WaitOven(product))
+        } else {
+            Some(Task::Drop)
+        }
+    }
 
-        // - Ok we may need to chope or bake
-        if let Some(action) = self.find_item(missing_item) {
-            return action;
+    /// the next task for the command, from our hand and the kitchen
+    pub fn get_next_task(&self, command: &Command) -> Task {
+        let hand = &self.player_hand;
+        let tasks = command.get_tasks(hand);
+        // what is on a dish left on a table is already made
+        let partial_dish = hand
+            .is_empty()
+            .then(|| self.find_partial_dish(command))
+            .flatten();
+        let to_make = tasks
+            .iter()
+            .copied()
+            .filter(|item| item.get_recipe().is_some() && !self.is_available(*item))
+            .filter(|item| !partial_dish.is_some_and(|table| table.items.contains(item)))
+            .collect::<Vec<_>>();
+
+        // something without a dish: an ingredient of what we make, or something to put aside
+        if !hand.is_empty() && !hand.contains(&ItemType::Dish) {
+            return to_make
+                .iter()
+                .copied()
+                .chain(self.get_lookahead_product())
+                .find(|product| *hand != [*product] && product.get_chain().contains(&hand[0]))
+                .map_or(Task::Drop, |product| self.get_recipe_task(product));
         }
-        if missing_item == ItemType::ChoppedStrawberries {
-            self.player_chopping_item = Some(ItemType::Strawberries);
-            return self.step();
+        // the dish has items the customer does not want, empty it
+        if !command.fits(hand) {
+            return Task::FetchItem(ItemType::Dish);
         }
-        if missing_item == ItemType::Croissant {
-            if self.is_oven_used() {
-                if let Some(next_missing) = diff.pop() {
-                    return self.find_item(next_missing).unwrap_or("WAIT".into());
-                } else {
-                    return "WAIT".into();
+        if tasks.is_empty() {
+            return Task::Deliver;
+        }
+
+        if let Some(table) = partial_dish.filter(|_| to_make.is_empty()) {
+            return Task::TakeDish(table.pos);
+        }
+
+        // make what is not available yet, once we have nothing else to get with our dish
+        if let Some(product) = to_make.first() {
+            let has_other_tasks = hand.contains(&ItemType::Dish)
+                && tasks
+                    .iter()
+                    .any(|item| item != product && self.is_available(*item));
+            if !has_other_tasks {
+                if !hand.is_empty() {
+                    return Task::Drop;
                 }
+                return self.get_recipe_task(*product);
             }
-            self.player_baking_item = Some(ItemType::Dough);
-            return self.step();
         }
 
-        return self.find_item(missing_item).unwrap_or("WAIT".into());
+        let missing_item = tasks
+            .iter()
+            .copied()
+            .find(|item| self.is_available(*item))
+            .unwrap_or(tasks[0]);
+        match missing_item {
+            _ if hand.is_empty() => Task::FetchItem(missing_item),
+            _ if self.is_being_baked(missing_item) => Task::WaitOven(missing_item),
+            _ => Task::Assemble(missing_item),
+        }
+    }
+
+    /// the action to get the task done
+    pub fn get_task_action(&self, task: Task) -> Action {
+        let oven = self.oven.as_ref().map(|oven| oven.pos);
+        match task {
+            Task::FetchItem(ItemType::Dish) if !self.player_hand.is_empty() => Action::Use(
+                *self
+                    .crates
+                    .get(&ItemType::Dish)
+                    .expect("no dishwasher found!"),
+            ),
+            Task::TakeDish(pos) => Action::Use(pos),
+            Task::FetchItem(item) | Task::Assemble(item) => {
+                self.find_item(item).unwrap_or(Action::Wait)
+            }
+            Task::Chop(_) => self
+                .choppers
+                .first()
+                .map_or(Action::Wait, |c| Action::Use(*c)),
+            Task::Bake(_) if self.is_oven_used() => oven.map_or(Action::Wait, |oven| {
+                debug!("-> oven is used, waiting next to it");
+                self.stage_near(&oven)
+            }),
+            Task::Bake(_) => oven.map_or(Action::Wait, Action::Use),
+            Task::WaitOven(item) => oven.map_or(Action::Wait, |pos| {
+                if self.find_item(item) == Some(Action::Use(pos)) {
+                    Action::Use(pos)
+                } else {
+                    self.stage_near(&pos)
+                }
+            }),
+            Task::Drop => self
+                .find_drop_table(self.get_target_after_drop())
+                .map_or(Action::Wait, Action::Use),
+            Task::Deliver => Action::Use(self.windows[0]),
+        }
     }
 
-    fn find_item(&self, missing_item: ItemType) -> Option<String> {
+    pub fn step(&mut self) -> Action {
+        // find a command, keep the one we picked while its customer is waiting
+        let is_waiting = self.player_command.as_ref().is_some_and(|picked| {
+            self.commands
+                .iter()
+                .any(|command| command.order == picked.order)
+        });
+        if !is_waiting {
+            debug!("-> no command picked yet, taking one");
+            self.player_command = self.select_command();
+            self.player_task = None;
+        }
+        let Some(command) = self.player_command.clone() else {
+            return Action::Wait;
+        };
+
+        debug!("player_hand: {:?}", self.player_hand);
+        debug!("command_order: {:?}", command.order);
+
+        let oven_product = self.oven.as_ref().and_then(|oven| oven.get_product());
+        if self.player_baking != oven_product {
+            self.player_baking = None;
+        }
+
+        // the current task ends once done, or when the kitchen changed under our feet
+        if let Some(task) = self.player_task {
+            if task.is_done(self) {
+                debug!("-> task {:?} is done", task);
+                if let Task::Bake(product) = task {
+                    self.player_baking = Some(product);
+                }
+                self.player_task = None;
+            } else if !task.is_valid(self) {
+                debug!("-> task {:?} is not valid anymore", task);
+                self.player_task = None;
+            }
+        }
+        // going back to the oven comes first once our item is about to burn
+        if let Some(task) = self.get_oven_task(&command) {
+            if self.player_task != Some(task) {
+                debug!("-> the oven needs us: {:?}", task);
+                self.player_task = Some(task);
+            }
+        }
+        if matches!(
+            self.player_task,
+            None | Some(Task::FetchItem(_) | Task::TakeDish(_) | Task::WaitOven(_))
+        ) {
+            if let Some(task) = self.get_lookahead_task(&command) {
+                if self.player_task != Some(task) {
+                    debug!(
+                        "-> the oven bakes, preparing for the next customers: {:?}",
+                        task
+                    );
+                    self.player_task = Some(task);
+                }
+            }
+        }
+        if self.player_task.is_none() {
+            let task = self.get_next_task(&command);
+            if !task.is_valid(self) {
+                debug!("-> next task {:?} is not valid, waiting", task);
+                return Action::Wait;
+            }
+            info!("-> next task: {:?}", task);
+            self.player_task = Some(task);
+        }
+        self.get_task_action(self.player_task.expect("a task is set"))
+    }
+
+    fn find_item(&self, missing_item: ItemType) -> Option<Action> {
         // - Table ?
-        eprintln!("-> finding a table with item {:?}", missing_item);
-        for table in &self.tables {
+        debug!("-> finding a table with item {:?}", missing_item);
+        // the closest table first, the partner may have dropped what we need
+        let mut tables = self.tables.iter().collect::<Vec<_>>();
+        tables.sort_by_key(|table| table.pos.manhattan_distance(&self.player_pos));
+        for table in tables {
             if table.items.contains(&missing_item) {
-                eprintln!("-> table contains the items: {:?}", table);
-                // TODO:
+                trace!("-> table contains the items: {:?}", table);
+                // a dish with items can only be taken whole, see `find_partial_dish`
                 if table.items.len() > 1 {
-                    eprintln!(
-                        "-> this table contains items but with other, skipping this table for now"
-                    );
+                    trace!("-> this table holds a dish, skipping it");
                     continue;
                 }
 
-                return Some(format!("USE {} {}", table.pos.x, table.pos.y));
+                return Some(Action::Use(table.pos));
+            }
+        }
+        // - Oven, once baked
+        if let Some(oven) = &self.oven {
+            if oven.is_baked() && oven.content == Some(missing_item) {
+                return Some(Action::Use(oven.pos));
             }
         }
         // - Crate then
-        eprintln!("-> not finding a table with item, going into crate");
+        debug!("-> not finding a table with item, going into crate");
         if let Some(crate_pos) = self.crates.get(&missing_item) {
-            return Some(format!("USE {} {}", crate_pos.x, crate_pos.y));
+            return Some(Action::Use(*crate_pos));
+        }
+
+        debug!("-> not finding item");
+        None
+    }
+}
+
+/// reads the customers and the kitchen, and the first line of the first turn that ends the kitchen rows
+fn read_game(lines: &mut impl Iterator<Item = String>) -> Option<(Game, String)> {
+    let num_all_customers = parse_input!(lines.next()?, i32);
+    let mut customers = Vec::new();
+    for i in 0..num_all_customers as usize {
+        customers.push(Command::decode(i as i32, lines.next()?));
+    }
+    // the kitchen rows go on until the first turn starts with the turns remaining
+    let mut kitchen_lines = Vec::new();
+    let first_turn_line = loop {
+        let input_line = lines.next()?;
+        if input_line.trim().parse::<i32>().is_ok() {
+            break input_line;
         }
+        kitchen_lines.push(input_line.trim_matches('\n').to_string());
+    };
+    let width = kitchen_lines.first().map_or(0, |line| line.chars().count());
+    let mut game = Game::new(width as i32, kitchen_lines.len() as i32);
+    for customer in customers {
+        game.add_customer(customer);
+    }
+    for (y, kitchen_line) in kitchen_lines.into_iter().enumerate() {
+        game.decode_row(y as i32, kitchen_line);
+    }
+    game.set_kitchen_paths();
+    Some((game, first_turn_line))
+}
 
-        eprintln!("-> not finding item");
-        return None;
+/// reads the turn after its first line (the turns remaining) into the game
+fn read_turn(
+    game: &mut Game,
+    first_line: String,
+    lines: &mut impl Iterator<Item = String>,
+) -> Option<()> {
+    game.start_new_loop();
+    let _turns_remaining = parse_input!(first_line, i32);
+    let input_line = lines.next()?;
+    let inputs = input_line.split(" ").collect::<Vec<_>>();
+    let player_x = parse_input!(inputs[0], i32);
+    let player_y = parse_input!(inputs[1], i32);
+    game.set_player_pos(Point::new(player_x, player_y));
+    let player_item = inputs[2].trim().to_string();
+    game.set_player_hand(ItemType::decode_full_item(player_item));
+    let input_line = lines.next()?;
+    let inputs = input_line.split(" ").collect::<Vec<_>>();
+    let partner_x = parse_input!(inputs[0], i32);
+    let partner_y = parse_input!(inputs[1], i32);
+    game.set_partner_pos(Point::new(partner_x, partner_y));
+    let partner_item = inputs[2].trim().to_string();
+    game.set_partner_hand(ItemType::decode_full_item(partner_item));
+    let num_tables_with_items = parse_input!(lines.next()?, i32); // the number of tables in the kitchen that currently hold an item
+    for _ in 0..num_tables_with_items as usize {
+        let input_line = lines.next()?;
+        let inputs = input_line.split(" ").collect::<Vec<_>>();
+        let table_x = parse_input!(inputs[0], i32);
+        let table_y = parse_input!(inputs[1], i32);
+        let item = inputs[2].trim().to_string();
+        let items = ItemType::decode_full_item(item);
+        game.add_table(Table::new(items, Point::new(table_x, table_y)))
+    }
+    let input_line = lines.next()?;
+    if let Some(oven) = &mut game.oven {
+        oven.update_from_raw(input_line);
+    }
+    let num_customers = parse_input!(lines.next()?, i32); // the number of customers currently waiting for food
+    for i in 0..num_customers as usize {
+        let input_line = lines.next()?;
+        debug!("adding a command: {}", input_line);
+        game.add_command(Command::decode(i as i32, input_line))
     }
+    game.update_customers_seen();
+    Some(())
 }
 
 /**
@@ -772,86 +1706,345 @@ impl Game {
  * the standard input according to the problem statement.
  **/
 fn main() {
-    let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
-    let num_all_customers = parse_input!(input_line, i32);
-    let mut game = Game::new(11, 7);
-    for i in 0..num_all_customers as usize {
+    // the end of the input ends the lines, the referee stopped the game
+    let mut lines = std::iter::from_fn(|| {
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
-        let inputs = input_line.split(" ").collect::<Vec<_>>();
-        let customer_item = inputs[0].trim().to_string(); // the food the customer is waiting for
-        let customer_award = parse_input!(inputs[1], i32); // the number of points awarded for delivering the food
-    }
-    for y in 0..7 as usize {
-        let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
-        let kitchen_line = input_line.trim_matches('\n').to_string();
-        game.decode_row(y as i32, kitchen_line);
-    }
+        (transcript::read_line(&mut input_line) > 0).then_some(input_line)
+    });
+    let Some((mut game, first_turn_line)) = read_game(&mut lines) else {
+        panic!("the input ended before the first turn");
+    };
+    let mut first_turn_line = Some(first_turn_line);
+    let mut turn = 0;
 
     // game loop
     loop {
-        game.start_new_loop();
-        let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
-        let turns_remaining = parse_input!(input_line, i32);
-        let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
-        let inputs = input_line.split(" ").collect::<Vec<_>>();
-        let player_x = parse_input!(inputs[0], i32);
-        let player_y = parse_input!(inputs[1], i32);
-        game.set_player_pos(Point::new(player_x, player_y));
-        let player_item = inputs[2].trim().to_string();
-        game.set_player_hand(ItemType::decode_full_item(player_item));
-        let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
-        let inputs = input_line.split(" ").collect::<Vec<_>>();
-        let partner_x = parse_input!(inputs[0], i32);
-        let partner_y = parse_input!(inputs[1], i32);
-        game.set_partner_pos(Point::new(partner_x, partner_y));
-        let partner_item = inputs[2].trim().to_string();
-        game.set_partner_hand(ItemType::decode_full_item(partner_item));
-        let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
-        let num_tables_with_items = parse_input!(input_line, i32); // the number of tables in the kitchen that currently hold an item
-        for _ in 0..num_tables_with_items as usize {
-            let mut input_line = String::new();
-            io::stdin().read_line(&mut input_line).unwrap();
-            let inputs = input_line.split(" ").collect::<Vec<_>>();
-            let table_x = parse_input!(inputs[0], i32);
-            let table_y = parse_input!(inputs[1], i32);
-            let item = inputs[2].trim().to_string();
-            let items = ItemType::decode_full_item(item);
-            game.add_table(Table::new(items, Point::new(table_x, table_y)))
-        }
-        let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
-        if let Some(oven) = &mut game.oven {
-            oven.update_from_raw(input_line);
-        }
-        let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
-        let num_customers = parse_input!(input_line, i32); // the number of customers currently waiting for food
-        for i in 0..num_customers as usize {
-            let mut input_line = String::new();
-            io::stdin().read_line(&mut input_line).unwrap();
-            // let inputs = input_line.split(" ").collect::<Vec<_>>();
-            // let customer_item = inputs[0].trim().to_string();
-            // let customer_award = parse_input!(inputs[1], i32);
-            eprintln!("adding a command: {}", input_line);
-            game.add_command(Command::decode(i as i32, input_line))
+        turn += 1;
+        let Some(input_line) = first_turn_line.take().or_else(|| lines.next()) else {
+            break;
+        };
+        if read_turn(&mut game, input_line, &mut lines).is_none() {
+            break;
         }
+        game.timer = TurnTimer::for_turn(timer::CODE_A_LA_MODE, turn);
 
-        eprintln!("{:?}", game.grid);
-        eprintln!("{:?}", &game.oven);
-
-        // Write an action using println!("message...");
-        // To debug: eprintln!("Debug message...");
+        trace!(target: "grid", "{:?}", game.grid);
+        debug!("{:?}", &game.oven);
 
         // MOVE x y
         // USE x y
         // WAIT
-        println!("{}", game.step());
+        let action = game.step();
+        write_answer!("{}", game.plan(action));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the default kitchen of the referee
+    const KITCHEN: [&str; 7] = [
+        "#####D##B##",
+        "#0........#",
+        "#.####.##.I",
+        "S.#C.#..#.#",
+        "#.##.####.#",
+        "#........1#",
+        "####W##OH##",
+    ];
+    const ORDER: &str = "DISH-CHOPPED_STRAWBERRIES-CROISSANT 1000";
+
+    /// the lines of a turn as the referee sends them, the partner stands still in the bottom right corner
+    fn get_turn_lines(
+        player: &str,
+        tables: &[&str],
+        oven: &str,
+        customers: &[&str],
+    ) -> Vec<String> {
+        let mut lines = vec![
+            "200".to_string(),
+            player.to_string(),
+            "9 5 NONE".to_string(),
+        ];
+        lines.push(tables.len().to_string());
+        lines.extend(tables.iter().map(|table| table.to_string()));
+        lines.push(oven.to_string());
+        lines.push(customers.len().to_string());
+        lines.extend(customers.iter().map(|customer| customer.to_string()));
+        lines
+    }
+
+    /// reads the start of the game then its first turn
+    fn start(customers: &[&str], kitchen: &[&str], turn: Vec<String>) -> Game {
+        let mut lines = [customers.len().to_string()]
+            .into_iter()
+            .chain(customers.iter().map(|customer| customer.to_string()))
+            .chain(kitchen.iter().map(|row| row.to_string()))
+            .chain(turn);
+        let (mut game, first_turn_line) = read_game(&mut lines).expect("the game is read");
+        read_turn(&mut game, first_turn_line, &mut lines).expect("the turn is read");
+        game
+    }
+
+    fn feed(game: &mut Game, turn: Vec<String>) {
+        let mut lines = turn.into_iter();
+        let first_line = lines.next().expect("a turn has lines");
+        read_turn(game, first_line, &mut lines).expect("the turn is read");
+    }
+
+    /// a game at the first turn with a single customer waiting
+    fn get_game(player: &str, tables: &[&str], oven: &str, order: &str) -> Game {
+        start(
+            &[order],
+            &KITCHEN,
+            get_turn_lines(player, tables, oven, &[order]),
+        )
+    }
+
+    #[test]
+    fn reads_the_kitchen_and_the_turn() {
+        let game = get_game("1 1 DISH", &["4 2 CHOPPED_STRAWBERRIES"], "DOUGH 4", ORDER);
+        assert_eq!((game.grid.width, game.grid.height), (11, 7));
+        assert_eq!(game.windows, vec![Point::new(4, 6)]);
+        assert_eq!(game.choppers, vec![Point::new(3, 3)]);
+        assert_eq!(game.crates.get(&ItemType::Dish), Some(&Point::new(5, 0)));
+        assert_eq!(game.crates.get(&ItemType::Dough), Some(&Point::new(8, 6)));
+        assert_eq!(
+            game.oven.as_ref().map(|oven| oven.pos),
+            Some(Point::new(7, 6))
+        );
+        assert_eq!(game.player_pos, Point::new(1, 1));
+        assert_eq!(game.player_hand, vec![ItemType::Dish]);
+        assert_eq!(game.partner_pos, Some(Point::new(9, 5)));
+        assert_eq!(game.tables.len(), 1);
+        assert!(game.is_being_baked(ItemType::Croissant));
+        assert_eq!(game.commands.len(), 1);
+        assert_eq!(game.customers_seen, 1);
+    }
+
+    #[test]
+    fn chokepoints_and_traps_come_from_the_empty_kitchen() {
+        // every crate is at the end of a corridor
+        let kitchen = [
+            "#####W#####",
+            "#0.......1#",
+            "#.#######.#",
+            "#.........#",
+            "####D.B####",
+            "####S.I####",
+            "####C.H####",
+            "#####O#####",
+        ];
+        let mut turn = get_turn_lines("5 3 NONE", &[], "NONE 0", &[ORDER]);
+        turn[2] = "5 5 NONE".to_string();
+        let game = start(&[ORDER], &kitchen, turn);
+        assert!(game.is_chokepoint(&Point::new(5, 4)));
+        assert!(game.is_chokepoint(&Point::new(4, 3)));
+        assert!(!game.is_chokepoint(&Point::new(5, 3)));
+        // the partner standing next to it does not change it
+        assert!(game.is_chokepoint(&Point::new(5, 6)));
+        assert!(game.is_partner_trapped(&Point::new(5, 3)));
+        assert!(game.is_partner_trapped(&Point::new(5, 4)));
+        assert!(!game.is_partner_trapped(&Point::new(4, 3)));
+        assert!(!game.is_partner_trapped(&Point::new(1, 1)));
+        // stepping aside to a corridor when no open cell is close
+        assert_eq!(
+            game.yield_to_partner(),
+            Some(Action::Move(Point::new(4, 3)))
+        );
+    }
+
+    #[test]
+    fn a_command_goes_through_every_task() {
+        let order = [ORDER];
+        let mut game = get_game("1 1 NONE", &[], "NONE 0", ORDER);
+        let play = |game: &mut Game, task: Task, action: Action| {
+            assert_eq!(game.step(), action);
+            assert_eq!(game.player_task, Some(task));
+        };
+
+        // the chopped strawberries first, they do not need the dish
+        let strawberries = Task::FetchItem(ItemType::Strawberries);
+        play(&mut game, strawberries, Action::Use(Point::new(0, 3)));
+        feed(
+            &mut game,
+            get_turn_lines("1 3 STRAWBERRIES", &[], "NONE 0", &order),
+        );
+        assert!(strawberries.is_done(&game));
+        let chop = Task::Chop(ItemType::ChoppedStrawberries);
+        play(&mut game, chop, Action::Use(Point::new(3, 3)));
+
+        // put aside while we bake the croissant
+        feed(
+            &mut game,
+            get_turn_lines("4 3 CHOPPED_STRAWBERRIES", &[], "NONE 0", &order),
+        );
+        assert!(chop.is_done(&game));
+        let Action::Use(table) = game.step() else {
+            panic!("the hand goes on a table");
+        };
+        assert_eq!(game.player_task, Some(Task::Drop));
+        assert!(game
+            .grid
+            .get_cell(&table)
+            .is_some_and(|cell| cell.cell_type == CellType::Wall));
+        let table_line = format!("{} {} CHOPPED_STRAWBERRIES", table.x, table.y);
+        let tables = [table_line.as_str()];
+        feed(
+            &mut game,
+            get_turn_lines("4 3 NONE", &tables, "NONE 0", &order),
+        );
+        let dough = Task::FetchItem(ItemType::Dough);
+        play(&mut game, dough, Action::Use(Point::new(8, 6)));
+        feed(
+            &mut game,
+            get_turn_lines("8 5 DOUGH", &tables, "NONE 0", &order),
+        );
+        let bake = Task::Bake(ItemType::Croissant);
+        play(&mut game, bake, Action::Use(Point::new(7, 6)));
+
+        // the dish and the chopped strawberries while it bakes
+        feed(
+            &mut game,
+            get_turn_lines("7 5 NONE", &tables, "DOUGH 10", &order),
+        );
+        assert!(bake.is_done(&game));
+        let dish = Task::FetchItem(ItemType::Dish);
+        play(&mut game, dish, Action::Use(Point::new(5, 0)));
+        assert_eq!(game.player_baking, Some(ItemType::Croissant));
+        feed(
+            &mut game,
+            get_turn_lines("5 1 DISH", &tables, "DOUGH 6", &order),
+        );
+        let assemble = Task::Assemble(ItemType::ChoppedStrawberries);
+        play(&mut game, assemble, Action::Use(table));
+
+        // then we wait next to the oven
+        feed(
+            &mut game,
+            get_turn_lines("7 5 DISH-CHOPPED_STRAWBERRIES", &[], "DOUGH 2", &order),
+        );
+        assert!(assemble.is_done(&game));
+        let wait = Task::WaitOven(ItemType::Croissant);
+        play(&mut game, wait, Action::Wait);
+        feed(
+            &mut game,
+            get_turn_lines("7 5 DISH-CHOPPED_STRAWBERRIES", &[], "CROISSANT 10", &order),
+        );
+        play(&mut game, wait, Action::Use(Point::new(7, 6)));
+
+        feed(
+            &mut game,
+            get_turn_lines(
+                "7 5 DISH-CHOPPED_STRAWBERRIES-CROISSANT",
+                &[],
+                "NONE 0",
+                &order,
+            ),
+        );
+        assert!(wait.is_done(&game));
+        play(&mut game, Task::Deliver, Action::Use(Point::new(4, 6)));
+        feed(&mut game, get_turn_lines("4 5 NONE", &[], "NONE 0", &[]));
+        assert!(Task::Deliver.is_done(&game));
+        assert_eq!(game.step(), Action::Wait);
+    }
+
+    #[test]
+    fn tasks_are_not_valid_with_the_wrong_hand_or_kitchen() {
+        let game = get_game("1 1 NONE", &[], "NONE 0", ORDER);
+        assert!(!Task::Chop(ItemType::ChoppedStrawberries).is_valid(&game));
+        assert!(!Task::Drop.is_valid(&game));
+        assert!(!Task::Deliver.is_valid(&game));
+        assert!(!Task::WaitOven(ItemType::Croissant).is_valid(&game));
+        assert!(!Task::FetchItem(ItemType::Croissant).is_valid(&game));
+        assert!(!Task::Assemble(ItemType::IceCream).is_valid(&game));
+        assert!(!Task::TakeDish(Point::new(4, 2)).is_valid(&game));
+
+        let game = get_game("1 3 STRAWBERRIES", &[], "NONE 0", ORDER);
+        assert!(Task::Chop(ItemType::ChoppedStrawberries).is_valid(&game));
+        assert!(!Task::Bake(ItemType::Croissant).is_valid(&game));
+        assert!(!Task::Chop(ItemType::Croissant).is_valid(&game));
+        assert!(!Task::FetchItem(ItemType::Dough).is_valid(&game));
+
+        let game = get_game("8 5 DOUGH", &[], "NONE 0", ORDER);
+        assert!(Task::Bake(ItemType::Croissant).is_valid(&game));
+        assert!(!Task::Chop(ItemType::ChoppedStrawberries).is_valid(&game));
+
+        // a dish that misses an item, or holds one the customer does not want
+        let game = get_game("4 5 DISH-CHOPPED_STRAWBERRIES", &[], "NONE 0", ORDER);
+        assert!(!Task::Deliver.is_valid(&game));
+        assert!(!Task::Assemble(ItemType::ChoppedStrawberries).is_valid(&game));
+        assert!(!Task::Assemble(ItemType::Croissant).is_valid(&game));
+        let game = get_game("4 5 DISH-BLUEBERRIES", &[], "NONE 0", ORDER);
+        assert!(!Task::Deliver.is_valid(&game));
+        assert_eq!(
+            game.get_next_task(&game.commands[0]),
+            Task::FetchItem(ItemType::Dish)
+        );
+        assert_eq!(
+            game.get_task_action(Task::FetchItem(ItemType::Dish)),
+            Action::Use(Point::new(5, 0))
+        );
+    }
+
+    #[test]
+    fn a_task_that_is_not_valid_anymore_is_replaced() {
+        let order = [ORDER];
+        let mut game = get_game("1 3 STRAWBERRIES", &[], "NONE 0", ORDER);
+        game.step();
+        assert_eq!(
+            game.player_task,
+            Some(Task::Chop(ItemType::ChoppedStrawberries))
+        );
+        // we hold something else than the strawberries
+        feed(
+            &mut game,
+            get_turn_lines("1 3 DOUGH", &[], "NONE 0", &order),
+        );
+        assert_eq!(game.step(), Action::Use(Point::new(7, 6)));
+        assert_eq!(game.player_task, Some(Task::Bake(ItemType::Croissant)));
+        // the partner filled the oven first, we wait next to it
+        feed(
+            &mut game,
+            get_turn_lines("7 5 DOUGH", &[], "RAW_TART 5", &order),
+        );
+        assert_eq!(game.step(), Action::Wait);
+        assert_eq!(game.player_task, Some(Task::Bake(ItemType::Croissant)));
+    }
+
+    #[test]
+    fn a_small_kitchen_is_played_inside_its_walls() {
+        let kitchen = ["##DWI##", "#0...1#", "#.....#", "#######"];
+        let order = "DISH-ICE_CREAM 500";
+        let mut turn = get_turn_lines("1 1 NONE", &[], "NONE 0", &[order]);
+        turn[2] = "5 1 NONE".to_string();
+        let mut game = start(&[order], &kitchen, turn);
+        assert_eq!((game.grid.width, game.grid.height), (7, 4));
+        assert_eq!(game.windows, vec![Point::new(3, 0)]);
+        assert_eq!(game.crates.get(&ItemType::Dish), Some(&Point::new(2, 0)));
+        assert!(game.oven.is_none());
+        let command = game.commands[0].clone();
+        assert!(game.estimate_command_turns(&command).is_some());
+        assert_eq!(game.step(), Action::Use(Point::new(2, 0)));
+        assert_eq!(game.player_task, Some(Task::FetchItem(ItemType::Dish)));
+        feed(&mut game, {
+            let mut turn = get_turn_lines("2 1 DISH", &[], "NONE 0", &[order]);
+            turn[2] = "5 1 NONE".to_string();
+            turn
+        });
+        assert_eq!(game.step(), Action::Use(Point::new(4, 0)));
+    }
+
+    #[test]
+    fn the_end_of_the_input_is_not_a_kitchen_row() {
+        let mut lines = ["1", ORDER, "#####", "#0.1#"]
+            .into_iter()
+            .map(|line| line.to_string());
+        assert!(read_game(&mut lines).is_none());
+        // a game without its turn lines
+        let mut game = get_game("1 1 NONE", &[], "NONE 0", ORDER);
+        let mut lines = std::iter::empty();
+        assert!(read_turn(&mut game, "199".to_string(), &mut lines).is_none());
     }
 }
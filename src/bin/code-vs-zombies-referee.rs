@@ -0,0 +1,286 @@
+// local referee for https://www.codingame.com/ide/puzzle/code-vs-zombies
+//
+// usage: code-vs-zombies-referee [--seed N] [--verbose] <bot>
+//
+// the bot is a command (quoted if it has arguments) talking over stdin/stdout, the seed places Ash, the humans and
+// the zombies at random on the map
+//
+// rules implemented here:
+// - the map is 16000x9000, Ash moves up to 1000 units toward his target, a zombie 400 toward the closest human
+//   (Ash included), a move shorter than its target ends on it, a longer one is truncated
+// - a turn: the zombies move, Ash moves, the zombies within 2000 units of Ash are killed, the zombies on a human
+//   eat it (Ash is never eaten)
+// - a zombie killed is worth 10 times the square of the humans alive, the n-th kill of a turn is multiplied by the
+//   (n+2)-th number of Fibonacci (1, 2, 3, 5, 8...)
+// - the game ends once every zombie or every human is dead, the score is 0 if no human is left
+// - a bot dying or giving a bad answer loses the game
+
+use codinggame_backtocode_rust::process::BotProcess;
+use codinggame_backtocode_rust::random::Random;
+use std::env;
+
+const WIDTH: i32 = 16000;
+const HEIGHT: i32 = 9000;
+const ASH_SPEED: f64 = 1000.;
+const ZOMBIE_SPEED: f64 = 400.;
+const SHOOT_RANGE: i64 = 2000;
+/// a game still going after this number of turns stops with its score so far
+const MAX_TURNS: i32 = 200;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn get_squared_distance(&self, pos: &Point) -> i64 {
+        (i64::from(pos.x - self.x)).pow(2) + (i64::from(pos.y - self.y)).pow(2)
+    }
+
+    /// the position after moving up to `speed` toward the target
+    pub fn move_toward(&self, target: &Point, speed: f64) -> Point {
+        let distance = (self.get_squared_distance(target) as f64).sqrt();
+        if distance <= speed {
+            return *target;
+        }
+        let ratio = speed / distance;
+        Point::new(
+            (f64::from(self.x) + f64::from(target.x - self.x) * ratio).floor() as i32,
+            (f64::from(self.y) + f64::from(target.y - self.y) * ratio).floor() as i32,
+        )
+    }
+}
+
+struct Referee {
+    ash: Point,
+    /// the humans alive, with their id
+    humans: Vec<(i32, Point)>,
+    /// the zombies alive, with their id
+    zombies: Vec<(i32, Point)>,
+    score: i64,
+}
+
+impl Referee {
+    /// `human_count` humans and `zombie_count` zombies anywhere on the map
+    pub fn generate(random: &mut Random, human_count: i32, zombie_count: i32) -> Self {
+        let mut get_point = || Point::new(random.range(0, WIDTH), random.range(0, HEIGHT));
+        let ash = get_point();
+        let humans = (0..human_count).map(|id| (id, get_point())).collect();
+        let zombies = (0..zombie_count).map(|id| (id, get_point())).collect();
+        Self {
+            ash,
+            humans,
+            zombies,
+            score: 0,
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.humans.is_empty() || self.zombies.is_empty()
+    }
+
+    /// where the zombie goes this turn: toward the closest human, Ash included (he wins a tie)
+    pub fn get_zombie_next_pos(&self, zombie: &Point) -> Point {
+        let target = self
+            .humans
+            .iter()
+            .map(|(_, pos)| pos)
+            .fold(self.ash, |closest, pos| {
+                if zombie.get_squared_distance(pos) < zombie.get_squared_distance(&closest) {
+                    *pos
+                } else {
+                    closest
+                }
+            });
+        zombie.move_toward(&target, ZOMBIE_SPEED)
+    }
+
+    /// the lines the bot reads every turn
+    pub fn get_turn_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("{} {}", self.ash.x, self.ash.y)];
+        lines.push(self.humans.len().to_string());
+        for (id, pos) in &self.humans {
+            lines.push(format!("{} {} {}", id, pos.x, pos.y));
+        }
+        lines.push(self.zombies.len().to_string());
+        for (id, pos) in &self.zombies {
+            let next_pos = self.get_zombie_next_pos(pos);
+            lines.push(format!(
+                "{} {} {} {} {}",
+                id, pos.x, pos.y, next_pos.x, next_pos.y
+            ));
+        }
+        lines
+    }
+
+    /// plays a turn with Ash going toward the target, returns the zombies killed
+    pub fn play_turn(&mut self, target: Point) -> usize {
+        let moves = self
+            .zombies
+            .iter()
+            .map(|(_, pos)| self.get_zombie_next_pos(pos))
+            .collect::<Vec<_>>();
+        for ((_, pos), next_pos) in self.zombies.iter_mut().zip(moves) {
+            *pos = next_pos;
+        }
+        let target = Point::new(target.x.clamp(0, WIDTH - 1), target.y.clamp(0, HEIGHT - 1));
+        self.ash = self.ash.move_toward(&target, ASH_SPEED);
+
+        let worth = 10 * (self.humans.len() as i64).pow(2);
+        let (mut multiplier, mut next_multiplier) = (1, 2);
+        let mut killed = 0;
+        let ash = self.ash;
+        self.zombies.retain(|(_, pos)| {
+            if pos.get_squared_distance(&ash) > SHOOT_RANGE * SHOOT_RANGE {
+                return true;
+            }
+            self.score += worth * multiplier;
+            (multiplier, next_multiplier) = (next_multiplier, multiplier + next_multiplier);
+            killed += 1;
+            false
+        });
+
+        let zombies = &self.zombies;
+        self.humans
+            .retain(|(_, human)| zombies.iter().all(|(_, zombie)| zombie != human));
+        killed
+    }
+}
+
+fn main() {
+    let mut seed = 0;
+    let mut verbose = false;
+    let mut bots = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = args.next().expect("--seed needs a value").parse().unwrap(),
+            "--verbose" => verbose = true,
+            _ => bots.push(arg),
+        }
+    }
+    if bots.len() != 1 {
+        eprintln!("usage: code-vs-zombies-referee [--seed N] [--verbose] <bot>");
+        std::process::exit(2);
+    }
+
+    let mut random = Random::new(seed);
+    let human_count = random.range(1, 11);
+    let zombie_count = random.range(1, 21);
+    let mut referee = Referee::generate(&mut random, human_count, zombie_count);
+    let mut bot = BotProcess::spawn(&bots[0], verbose);
+
+    let mut turn = 0;
+    let mut lost = false;
+    while !referee.is_over() && turn < MAX_TURNS {
+        turn += 1;
+        bot.send(&referee.get_turn_lines());
+        let Some(answer) = bot.receive() else {
+            eprintln!("turn {}: the bot died", turn);
+            lost = true;
+            break;
+        };
+        let mut values = answer.split_whitespace().map(str::parse::<i32>);
+        let (Some(Ok(x)), Some(Ok(y))) = (values.next(), values.next()) else {
+            eprintln!("turn {}: bad answer `{}`", turn, answer);
+            lost = true;
+            break;
+        };
+        referee.play_turn(Point::new(x, y));
+    }
+
+    let score = if lost || referee.humans.is_empty() {
+        0
+    } else {
+        referee.score
+    };
+    println!(
+        "{} turns, {}/{} humans saved, {}/{} zombies killed",
+        turn,
+        referee.humans.len(),
+        human_count,
+        zombie_count as usize - referee.zombies.len(),
+        zombie_count
+    );
+    println!("score: {}", score);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_referee(ash: Point, humans: &[Point], zombies: &[Point]) -> Referee {
+        Referee {
+            ash,
+            humans: humans
+                .iter()
+                .copied()
+                .zip(0..)
+                .map(|(pos, id)| (id, pos))
+                .collect(),
+            zombies: zombies
+                .iter()
+                .copied()
+                .zip(0..)
+                .map(|(pos, id)| (id, pos))
+                .collect(),
+            score: 0,
+        }
+    }
+
+    #[test]
+    fn a_move_ends_on_a_close_target_and_is_truncated_otherwise() {
+        let pos = Point::new(0, 0);
+        assert_eq!(
+            pos.move_toward(&Point::new(300, 0), ZOMBIE_SPEED),
+            Point::new(300, 0)
+        );
+        assert_eq!(
+            pos.move_toward(&Point::new(3000, 4000), ASH_SPEED),
+            Point::new(600, 800)
+        );
+        assert_eq!(
+            pos.move_toward(&Point::new(1000, 1000), ZOMBIE_SPEED),
+            Point::new(282, 282)
+        );
+        assert_eq!(
+            Point::new(1000, 1000).move_toward(&Point::new(0, 0), ZOMBIE_SPEED),
+            Point::new(717, 717)
+        );
+    }
+
+    #[test]
+    fn zombies_go_for_the_closest_human_and_eat_it() {
+        let mut referee = get_referee(
+            Point::new(0, 0),
+            &[Point::new(10000, 5000), Point::new(15000, 5000)],
+            &[Point::new(10300, 5000), Point::new(2800, 0)],
+        );
+        assert_eq!(
+            referee.get_turn_lines()[4..],
+            ["2", "0 10300 5000 10000 5000", "1 2800 0 2400 0"]
+        );
+        assert_eq!(referee.play_turn(Point::new(0, 0)), 0);
+        assert_eq!(referee.humans, vec![(1, Point::new(15000, 5000))]);
+        // the zombie after Ash is shot once in range
+        assert_eq!(referee.zombies[1].1, Point::new(2400, 0));
+        assert_eq!(referee.play_turn(Point::new(0, 0)), 1);
+        assert_eq!(referee.score, 10);
+        assert!(!referee.is_over());
+    }
+
+    #[test]
+    fn the_kills_of_a_turn_are_multiplied_by_fibonacci_numbers() {
+        let zombies = [Point::new(1000, 0); 5];
+        let mut referee = get_referee(Point::new(0, 0), &[Point::new(8000, 8000); 2], &zombies);
+        assert_eq!(referee.play_turn(Point::new(0, 0)), 5);
+        assert_eq!(referee.score, 40 * (1 + 2 + 3 + 5 + 8));
+        assert!(referee.is_over());
+    }
+}
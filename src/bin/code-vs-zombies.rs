@@ -1,6 +1,7 @@
 // https://www.codingame.com/ide/puzzle/code-vs-zombies
 
-use std::{collections::HashMap, io};
+use codinggame_backtocode_rust::{params, transcript, write_answer};
+use std::collections::HashMap;
 
 macro_rules! parse_input {
     ($x:expr, $t:ident) => {
@@ -8,6 +9,12 @@ macro_rules! parse_input {
     };
 }
 
+/// the gun kills the zombies this close, a human is saved once we are this far from them
+const PLAYER_SHOOT_DISTANCE: i32 = 2000;
+/// a human is counted as reached this much inside the range of the gun, a margin for the zombies moving meanwhile,
+/// below 0 a human still out of range is counted as reached (tuned with `tune --game code-vs-zombies --seeds 300`)
+const SHOOT_SLACK: i32 = -1653;
+
 #[derive(Copy, Clone, Eq, Hash, PartialEq, Debug)]
 struct Point {
     x: i32,
@@ -28,95 +35,6 @@ impl Point {
     }
 }
 
-#[derive(Debug)]
-struct Distance {
-    from: Point,
-    to: Point,
-    distance: f32,
-}
-
-impl Distance {
-    pub fn new(from: Point, to: Point) -> Self {
-        Distance {
-            from,
-            to,
-            distance: from.get_distance(&to),
-        }
-    }
-
-    pub fn new_with_distance(from: Point, to: Point, distance: f32) -> Self {
-        Distance { from, to, distance }
-    }
-}
-
-#[derive(Copy, Clone, PartialEq)]
-enum CellType {
-    Empty,
-    Human,
-    Zombie,
-}
-
-#[derive(Copy, Clone)]
-struct Cell {
-    pos: Point,
-    cell_type: CellType,
-    zombies_count: usize,
-}
-
-impl Cell {
-    pub fn new(pos: Point) -> Self {
-        Self {
-            pos,
-            cell_type: CellType::Empty,
-            zombies_count: 0,
-        }
-    }
-
-    pub fn reset(&mut self) {
-        self.cell_type = CellType::Empty;
-        self.zombies_count = 0;
-    }
-}
-
-#[derive(Clone)]
-struct Grid {
-    height: i32,
-    width: i32,
-    cells: HashMap<Point, Cell>,
-}
-
-impl Grid {
-    pub fn new(w: i32, h: i32) -> Self {
-        let cells = HashMap::with_capacity((w * h) as usize);
-        Self {
-            height: h,
-            width: w,
-            cells: cells,
-        }
-    }
-
-    pub fn set_cell_type(&mut self, pos: &Point, cell_type: CellType) {
-        let cell = self.get_mut_cell(pos);
-        cell.cell_type = cell_type;
-        if cell.cell_type == CellType::Zombie {
-            cell.zombies_count += 1;
-        }
-    }
-
-    pub fn get_mut_cell(&mut self, pos: &Point) -> &mut Cell {
-        if self.cells.contains_key(pos) {
-            return self.cells.get_mut(pos).unwrap();
-        }
-
-        self.cells.insert(*pos, Cell::new(*pos));
-        self.cells.get_mut(pos).unwrap()
-    }
-
-    pub fn reset(&mut self) {
-        self.cells.clear();
-    }
-}
-
 #[derive(Copy, Clone, Debug)]
 struct Human {
     id: i32,
@@ -140,13 +58,12 @@ impl Human {
 #[derive(Copy, Clone)]
 struct Zombie {
     id: i32,
-    pos: Point,
     next_pos: Point,
 }
 
 impl Zombie {
-    pub fn new(id: i32, pos: Point, next_pos: Point) -> Self {
-        Self { id, pos, next_pos }
+    pub fn new(id: i32, next_pos: Point) -> Self {
+        Self { id, next_pos }
     }
 }
 
@@ -155,6 +72,7 @@ struct Game {
     player_pos: Point,
 
     player_shoot_distance: i32,
+    shoot_slack: i32,
     player_velocity: i32,
     zombie_velocity: i32,
 
@@ -163,28 +81,25 @@ struct Game {
 
     humans: HashMap<i32, Human>,
     zombies: HashMap<i32, Zombie>,
-
-    grid: Grid,
 }
 
 impl Game {
-    pub fn new(w: i32, h: i32) -> Self {
+    pub fn new() -> Self {
         Self {
-            player_shoot_distance: 2000,
+            player_shoot_distance: PLAYER_SHOOT_DISTANCE,
+            shoot_slack: params::get("shoot_slack", SHOOT_SLACK),
             player_velocity: 1000,
             zombie_velocity: 400,
             player_pos: Point::new(-1, -1),
             humans: HashMap::new(),
-            grid: Grid::new(w, h),
             zombies: HashMap::new(),
             prev_humans: HashMap::new(),
             prev_zombies: HashMap::new(),
         }
     }
 
-    /// reset grid for a new loop
+    /// forget the humans and zombies of the last turn for a new loop
     pub fn new_loop(&mut self) {
-        self.grid.reset();
         self.prev_humans = self.humans.clone();
         self.prev_zombies = self.zombies.clone();
         self.humans.clear();
@@ -193,46 +108,42 @@ impl Game {
 
     /// adds a human, if a human has been deleted, put it in dead_humans vec (useful for debug)
     pub fn add_human(&mut self, human: Human) {
-        self.grid.set_cell_type(&human.pos, CellType::Human);
         self.humans.insert(human.id, human);
     }
 
     /// adds a zombie, if a zombie has been deleted, put it in dead_zombies vec (useful for debug)
     pub fn add_zombie(&mut self, zombie: Zombie) {
-        self.grid.set_cell_type(&zombie.pos, CellType::Zombie);
         self.zombies.insert(zombie.id, zombie);
     }
 
     /// compute for each humans how many turns he could live
     pub fn compute_humans_life_expectancy(&mut self) {
         for (_, human) in self.humans.iter_mut() {
-            let closest_zombie_pos =
-                Game::get_closest_zombie_next_pos(self.zombies.values().collect(), &human.pos);
-            human.turns_to_zombie =
-                (closest_zombie_pos.distance as f32) / (self.zombie_velocity as f32);
+            let closest_zombie_distance =
+                Game::get_closest_zombie_distance(self.zombies.values().collect(), &human.pos);
+            human.turns_to_zombie = closest_zombie_distance / (self.zombie_velocity as f32);
         }
     }
 
     /// compute each human distance to player (in turns)
     pub fn compute_player_to_humans_turns(&mut self) {
-        for (_, human) in &mut self.humans {
-            human.turns_to_player = ((self.player_pos.get_distance(&human.pos) as f32)
-                - (self.player_shoot_distance as f32))
+        for human in self.humans.values_mut() {
+            human.turns_to_player = (self.player_pos.get_distance(&human.pos)
+                - ((self.player_shoot_distance - self.shoot_slack) as f32))
                 / (self.player_velocity as f32);
         }
     }
 
-    pub fn get_closest_zombie_next_pos(zombies: Vec<&Zombie>, pos: &Point) -> Distance {
+    /// the distance from the position to the next position of the closest zombie
+    pub fn get_closest_zombie_distance(zombies: Vec<&Zombie>, pos: &Point) -> f32 {
         let mut closest_distance = -1.;
-        let mut closest_point = Point::new(0, 0);
         for zombie in zombies {
             let distance = zombie.next_pos.get_distance(pos);
             if closest_distance == -1. || closest_distance > distance {
                 closest_distance = distance;
-                closest_point = zombie.pos;
             }
         }
-        Distance::new_with_distance(*pos, closest_point, closest_distance)
+        closest_distance
     }
 
     pub fn get_closest_rescuable_human_pos(&self) -> Option<Point> {
@@ -242,7 +153,7 @@ impl Game {
             .filter(|&human| human.turns_to_player < human.turns_to_zombie)
             .collect::<Vec<_>>();
         rescuable_humans.sort_by(|a, b| a.turns_to_player.total_cmp(&b.turns_to_player));
-        rescuable_humans.get(0).map(|human| human.pos)
+        rescuable_humans.first().map(|human| human.pos)
     }
 }
 
@@ -250,7 +161,7 @@ impl Game {
  * Save humans, destroy zombies!
  **/
 fn main() {
-    let mut game = Game::new(16000, 9000);
+    let mut game = Game::new();
 
     // game loop
     loop {
@@ -258,7 +169,7 @@ fn main() {
 
         // player pos
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         let inputs = input_line.split(" ").collect::<Vec<_>>();
         let x = parse_input!(inputs[0], i32);
         let y = parse_input!(inputs[1], i32);
@@ -267,11 +178,11 @@ fn main() {
 
         // humans
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         let human_count = parse_input!(input_line, i32);
         for _ in 0..human_count as usize {
             let mut input_line = String::new();
-            io::stdin().read_line(&mut input_line).unwrap();
+            transcript::read_line(&mut input_line);
             let inputs = input_line.split(" ").collect::<Vec<_>>();
             let human_id = parse_input!(inputs[0], i32);
             let human_x = parse_input!(inputs[1], i32);
@@ -281,20 +192,17 @@ fn main() {
 
         // zombies
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         let zombie_count = parse_input!(input_line, i32);
         for _ in 0..zombie_count as usize {
             let mut input_line = String::new();
-            io::stdin().read_line(&mut input_line).unwrap();
+            transcript::read_line(&mut input_line);
             let inputs = input_line.split(" ").collect::<Vec<_>>();
             let zombie_id = parse_input!(inputs[0], i32);
-            let zombie_x = parse_input!(inputs[1], i32);
-            let zombie_y = parse_input!(inputs[2], i32);
             let zombie_xnext = parse_input!(inputs[3], i32);
             let zombie_ynext = parse_input!(inputs[4], i32);
             game.add_zombie(Zombie::new(
                 zombie_id,
-                Point::new(zombie_x, zombie_y),
                 Point::new(zombie_xnext, zombie_ynext),
             ));
         }
@@ -307,9 +215,9 @@ fn main() {
         // To debug: eprintln!("Debug message...");
 
         if let Some(pos) = game.get_closest_rescuable_human_pos() {
-            println!("{} {}", pos.x, pos.y); // Your destination coordinates
+            write_answer!("{} {}", pos.x, pos.y); // Your destination coordinates
         } else {
-            println!("0 0 FUCK ME");
+            write_answer!("0 0 FUCK ME");
         }
     }
 }
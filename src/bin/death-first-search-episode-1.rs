@@ -1,9 +1,7 @@
 // https://www.codingame.com/ide/puzzle/death-first-search-episode-1
 
-use std::{
-    collections::{HashMap, HashSet},
-    io,
-};
+use codinggame_backtocode_rust::{transcript, warn, write_answer};
+use std::collections::{HashMap, HashSet};
 
 macro_rules! parse_input {
     ($x:expr, $t:ident) => {
@@ -38,20 +36,12 @@ impl Node {
         }
     }
 }
+#[derive(Default)]
 struct Game {
     pub grid: HashMap<i32, Node>,
     pub exits: Vec<i32>,
 }
 
-impl Default for Game {
-    fn default() -> Self {
-        Game {
-            grid: HashMap::new(),
-            exits: Vec::new(),
-        }
-    }
-}
-
 impl Game {
     pub fn create_empty_nodes(&mut self, n: i32) {
         for i in 0..n {
@@ -111,17 +101,13 @@ impl Game {
         }
         let mut next_nodes = Vec::<NodeWithParent>::new();
         let mut already_seen_nodes = HashSet::<i32>::new();
-        let Some(starting_node) = self.get_node(start) else {
-            return None;
-        };
+        let starting_node = self.get_node(start)?;
         next_nodes.push(NodeWithParent {
             node: starting_node,
             parent_id: *start,
         });
         loop {
-            let Some(nwp) = next_nodes.pop() else {
-                return None;
-            };
+            let nwp = next_nodes.pop()?;
             already_seen_nodes.insert(nwp.node.id);
             if nwp.node.is_exit {
                 return Some((nwp.parent_id, nwp.node.id));
@@ -155,7 +141,7 @@ impl Game {
 fn main() {
     let mut game = Game::default();
     let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
+    transcript::read_line(&mut input_line);
     let inputs = input_line.split(" ").collect::<Vec<_>>();
     let n = parse_input!(inputs[0], i32); // the total number of nodes in the level, including the gateways
     let l = parse_input!(inputs[1], i32); // the number of links
@@ -164,7 +150,7 @@ fn main() {
 
     for _ in 0..l as usize {
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         let inputs = input_line.split(" ").collect::<Vec<_>>();
         let n1 = parse_input!(inputs[0], i32); // N1 and N2 defines a link between these nodes
         let n2 = parse_input!(inputs[1], i32);
@@ -172,7 +158,7 @@ fn main() {
     }
     for _ in 0..e as usize {
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         let ei = parse_input!(input_line, i32); // the index of a gateway node
         game.add_exit(ei);
     }
@@ -180,15 +166,15 @@ fn main() {
     // game loop
     loop {
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         let si = parse_input!(input_line, i32); // The index of the node on which the Bobnet agent is positioned this turn
 
         if let Some((l, r)) = game.find_closest_node_toward_exit(&si) {
             game.remove_link(l, r);
-            println!("{} {}", l, r);
+            write_answer!("{} {}", l, r);
         } else {
-            eprintln!("WHAT");
-            println!("sorry");
+            warn!("WHAT");
+            write_answer!("sorry");
             panic!("should not happen")
         }
         // Write an action using println!("message...");
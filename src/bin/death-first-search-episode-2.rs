@@ -1,9 +1,7 @@
 // https://www.codingame.com/training/hard/death-first-search-episode-2
 
-use std::{
-    collections::{HashMap, HashSet, VecDeque},
-    io,
-};
+use codinggame_backtocode_rust::{debug, trace, transcript, write_answer};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 macro_rules! parse_input {
     ($x:expr, $t:ident) => {
@@ -51,20 +49,12 @@ impl Node {
     }
 }
 
+#[derive(Default)]
 struct Game {
     pub grid: HashMap<i32, Node>,
     pub exits: Vec<i32>,
 }
 
-impl Default for Game {
-    fn default() -> Self {
-        Game {
-            grid: HashMap::new(),
-            exits: Vec::new(),
-        }
-    }
-}
-
 impl Game {
     pub fn create_empty_nodes(&mut self, n: i32) {
         for i in 0..n {
@@ -144,7 +134,7 @@ impl Game {
                 }
             }
 
-            eprintln!("node: {} / exits: {}", node.id, exits_count);
+            trace!("node: {} / exits: {}", node.id, exits_count);
 
             if exits_count < highest_exits_count {
                 continue;
@@ -168,17 +158,13 @@ impl Game {
         }
         let mut next_nodes = Vec::<NodeWithParent>::new();
         let mut already_seen_nodes = HashSet::<i32>::new();
-        let Some(starting_node) = self.get_node(start) else {
-            return None;
-        };
+        let starting_node = self.get_node(start)?;
         next_nodes.push(NodeWithParent {
             node: starting_node,
             parent_id: None,
         });
         loop {
-            let Some(nwp) = next_nodes.pop() else {
-                return None;
-            };
+            let nwp = next_nodes.pop()?;
             already_seen_nodes.insert(nwp.node.id);
             if nwp.node.is_exit {
                 return Some((nwp.parent_id, nwp.node.id));
@@ -208,7 +194,7 @@ fn to_known_link_or_panic(link: (Option<i32>, i32)) -> (i32, i32) {
 fn main() {
     let mut game = Game::default();
     let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
+    transcript::read_line(&mut input_line);
     let inputs = input_line.split(" ").collect::<Vec<_>>();
     let n = parse_input!(inputs[0], i32); // the total number of nodes in the level, including the gateways
     let l = parse_input!(inputs[1], i32); // the number of links
@@ -217,7 +203,7 @@ fn main() {
 
     for _ in 0..l as usize {
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         let inputs = input_line.split(" ").collect::<Vec<_>>();
         let n1 = parse_input!(inputs[0], i32); // N1 and N2 defines a link between these nodes
         let n2 = parse_input!(inputs[1], i32);
@@ -225,7 +211,7 @@ fn main() {
     }
     for _ in 0..e as usize {
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         let ei = parse_input!(input_line, i32); // the index of a gateway node
         game.add_exit(ei);
     }
@@ -233,7 +219,7 @@ fn main() {
     // game loop
     loop {
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         let si = parse_input!(input_line, i32); // The index of the node on which the Bobnet agent is positioned this turn
 
         let mut link = to_known_link_or_panic(
@@ -242,14 +228,14 @@ fn main() {
         );
 
         if link.0 == si {
-            eprintln!("DO NOT HAVE TIME");
+            debug!("DO NOT HAVE TIME");
         } else {
-            eprintln!("we have time to find something better to cut");
+            debug!("we have time to find something better to cut");
             let (nodes, exit_counts) = game.get_nodes_with_more_exits(&si);
-            eprintln!("more_exits ({}): {:?}", exit_counts, nodes);
+            debug!("more_exits ({}): {:?}", exit_counts, nodes);
 
             let node = nodes
-                .get(0)
+                .first()
                 .unwrap_or_else(|| panic!("no more node with exit??"));
 
             link = to_known_link_or_panic(
@@ -258,7 +244,7 @@ fn main() {
             );
         }
 
-        println!("{} {}", link.0, link.1);
+        write_answer!("{} {}", link.0, link.1);
         game.remove_link(link.0, link.1);
     }
 }
@@ -1,6 +1,6 @@
 // https://www.codingame.com/ide/puzzle/don't-panic-episode-1
 
-use std::io;
+use codinggame_backtocode_rust::{transcript, write_answer};
 
 macro_rules! parse_input {
     ($x:expr, $t:ident) => {
@@ -24,7 +24,6 @@ impl Point {
 enum CellType {
     Empty,
     Elevator,
-    Clone, // A robot
 }
 
 struct Cell {
@@ -34,7 +33,6 @@ struct Cell {
 
 struct Game {
     pub width: i32,
-    pub heigh: i32,
     pub grid: Vec<Cell>,
 }
 
@@ -42,7 +40,6 @@ impl Game {
     pub fn new(w: i32, h: i32) -> Self {
         let mut game = Game {
             width: w,
-            heigh: h,
             grid: Vec::with_capacity((w * h) as usize),
         };
 
@@ -100,7 +97,7 @@ impl Game {
         for x in 0..self.width {
             let cell = self.get_cell(&Point { x, y });
             if cell.t == CellType::Elevator {
-                return Some(cell.position.clone());
+                return Some(cell.position);
             }
         }
         None
@@ -113,20 +110,20 @@ impl Game {
  **/
 fn main() {
     let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
+    transcript::read_line(&mut input_line);
     let inputs = input_line.split(" ").collect::<Vec<_>>();
     let nb_floors = parse_input!(inputs[0], i32); // number of floors
     let width = parse_input!(inputs[1], i32); // width of the area
     let mut game = Game::new(width, nb_floors);
-    let nb_rounds = parse_input!(inputs[2], i32); // maximum number of rounds
+    let _nb_rounds = parse_input!(inputs[2], i32); // maximum number of rounds
     let exit_floor = parse_input!(inputs[3], i32); // floor on which the exit is found
     let exit_pos = parse_input!(inputs[4], i32); // position of the exit on its floor
-    let nb_total_clones = parse_input!(inputs[5], i32); // number of generated clones
-    let nb_additional_elevators = parse_input!(inputs[6], i32); // ignore (always zero)
+    let _nb_total_clones = parse_input!(inputs[5], i32); // number of generated clones
+    let _nb_additional_elevators = parse_input!(inputs[6], i32); // ignore (always zero)
     let nb_elevators = parse_input!(inputs[7], i32); // number of elevators
     for _ in 0..nb_elevators as usize {
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         let inputs = input_line.split(" ").collect::<Vec<_>>();
         let elevator_floor = parse_input!(inputs[0], i32); // floor on which this elevator is found
         let elevator_pos = parse_input!(inputs[1], i32); // position of the elevator on its floor
@@ -138,14 +135,14 @@ fn main() {
     // game loop
     loop {
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         let inputs = input_line.split(" ").collect::<Vec<_>>();
         let clone_floor = parse_input!(inputs[0], i32); // floor of the leading clone
         let clone_pos = parse_input!(inputs[1], i32); // position of the leading clone on its floor
         let direction = inputs[2].trim().to_string(); // direction of the leading clone: LEFT or RIGHT
 
         if direction.as_str() == "NONE" {
-            println!("WAIT");
+            write_answer!("WAIT");
             continue;
         }
 
@@ -154,13 +151,13 @@ fn main() {
             match direction.as_str() {
                 "LEFT" => {
                     if clone_pos < exit_pos {
-                        println!("BLOCK");
+                        write_answer!("BLOCK");
                         continue;
                     }
                 }
                 "RIGHT" => {
                     if clone_pos > exit_pos {
-                        println!("BLOCK");
+                        write_answer!("BLOCK");
                         continue;
                     }
                 }
@@ -170,13 +167,13 @@ fn main() {
             match direction.as_str() {
                 "LEFT" => {
                     if clone_pos < next_elevator_position.x {
-                        println!("BLOCK");
+                        write_answer!("BLOCK");
                         continue;
                     }
                 }
                 "RIGHT" => {
                     if clone_pos > next_elevator_position.x {
-                        println!("BLOCK");
+                        write_answer!("BLOCK");
                         continue;
                     }
                 }
@@ -185,8 +182,8 @@ fn main() {
         }
         // }
 
-        println!("WAIT"); // action: WAIT or BLOCK
-                          // Write an action using println!("message...");
-                          // To debug: eprintln!("Debug message...");
+        write_answer!("WAIT"); // action: WAIT or BLOCK
+                               // Write an action using println!("message...");
+                               // To debug: eprintln!("Debug message...");
     }
 }
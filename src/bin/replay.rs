@@ -0,0 +1,116 @@
+// replays a transcript recorded by a bot (see `src/transcript.rs`) into a bot, and shows where its answers differ
+//
+// usage: replay [--verbose] [--budget MILLIS|game] <transcript> <bot>
+//
+// the bot is a command (quoted if it has arguments), it gets the recorded lines turn by turn and its answers are
+// compared to the recorded ones, the exit code is 1 if any answer differs
+//
+// the turn timers of the bot never run out so its searches end the same on every replay (`TURN_BUDGET=unlimited`,
+// see `src/timer.rs`), `--budget` gives every turn a fixed time instead or `game` keeps the budgets of the game
+//
+// a game is recorded by giving the bot a transcript file, with a referee:
+// code-a-la-mode-referee "target/release/code-a-la-mode --transcript game.txt"
+// replay game.txt target/release/code-a-la-mode
+
+use codinggame_backtocode_rust::process::BotProcess;
+use codinggame_backtocode_rust::timer;
+use std::env;
+use std::fs;
+use std::time::Instant;
+
+/// the lines sent to the bot before an answer, and the answer it gave
+struct Turn {
+    inputs: Vec<String>,
+    answer: String,
+    micros: u128,
+}
+
+fn parse_transcript(content: &str) -> Vec<Turn> {
+    let mut turns = Vec::new();
+    let mut inputs = Vec::new();
+    for line in content.lines() {
+        if let Some(input) = line.strip_prefix("> ") {
+            inputs.push(input.to_string());
+        } else if line == ">" {
+            inputs.push(String::new());
+        } else if let Some(output) = line.strip_prefix("< ") {
+            let (micros, answer) = output.split_once(' ').unwrap_or((output, ""));
+            turns.push(Turn {
+                inputs: std::mem::take(&mut inputs),
+                answer: answer.to_string(),
+                micros: micros.parse().unwrap_or(0),
+            });
+        }
+    }
+    turns
+}
+
+fn main() {
+    let mut verbose = false;
+    let mut budget = Some("unlimited".to_string());
+    let mut paths = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--verbose" => verbose = true,
+            "--budget" => {
+                let spec = args.next().expect("--budget needs milliseconds or game");
+                budget = (spec != "game").then_some(spec);
+            }
+            _ => paths.push(arg),
+        }
+    }
+    let [transcript, bot] = &paths[..] else {
+        eprintln!("usage: replay [--verbose] [--budget MILLIS|game] <transcript> <bot>");
+        std::process::exit(2);
+    };
+    if let Some(budget) = &budget {
+        timer::parse_budget(budget).expect("--budget needs milliseconds or game");
+    }
+    // inherited by the bot
+    match budget {
+        Some(budget) => env::set_var("TURN_BUDGET", budget),
+        None => env::remove_var("TURN_BUDGET"),
+    }
+
+    let content = fs::read_to_string(transcript)
+        .unwrap_or_else(|e| panic!("can not read {}: {}", transcript, e));
+    let turns = parse_transcript(&content);
+    let mut process = BotProcess::spawn(bot, verbose);
+
+    let mut differences = 0;
+    let mut slowest = (0, 0, 0);
+    for (index, turn) in turns.iter().enumerate() {
+        process.send(&turn.inputs);
+        let start = Instant::now();
+        let Some(answer) = process.receive() else {
+            println!("turn {}: the bot died", index + 1);
+            differences += turns.len() - index;
+            break;
+        };
+        let micros = start.elapsed().as_micros();
+        if micros > slowest.2 {
+            slowest = (index + 1, turn.micros, micros);
+        }
+        if answer != turn.answer {
+            println!(
+                "turn {}: recorded `{}`, replayed `{}`",
+                index + 1,
+                turn.answer,
+                answer
+            );
+            differences += 1;
+        }
+    }
+    println!(
+        "{} turns, {} differences, slowest turn {} ({}us recorded, {}us replayed)",
+        turns.len(),
+        differences,
+        slowest.0,
+        slowest.1,
+        slowest.2
+    );
+    if differences > 0 {
+        std::process::exit(1);
+    }
+}
@@ -1,6 +1,7 @@
 // https://www.codingame.com/ide/puzzle/shadows-of-the-knight-episode-1
 
-use std::{cmp, io};
+use codinggame_backtocode_rust::{debug, transcript, write_answer};
+use std::cmp;
 
 macro_rules! parse_input {
     ($x:expr, $t:ident) => {
@@ -14,15 +15,15 @@ macro_rules! parse_input {
  **/
 fn main() {
     let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
+    transcript::read_line(&mut input_line);
     let inputs = input_line.split(" ").collect::<Vec<_>>();
     let w = parse_input!(inputs[0], i32); // width of the building.
     let h = parse_input!(inputs[1], i32); // height of the building.
     let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
-    let n = parse_input!(input_line, i32); // maximum number of turns before game over.
+    transcript::read_line(&mut input_line);
+    let _n = parse_input!(input_line, i32); // maximum number of turns before game over.
     let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
+    transcript::read_line(&mut input_line);
     let inputs = input_line.split(" ").collect::<Vec<_>>();
     let mut x = parse_input!(inputs[0], i32);
     let mut y = parse_input!(inputs[1], i32);
@@ -35,7 +36,7 @@ fn main() {
     // game loop
     loop {
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         let bomb_dir = input_line.trim().to_string(); // the direction of the bombs from batman's current location (U, UR, R, DR, D, DL, L or UL)
         let bx = x;
         let by = y;
@@ -87,7 +88,7 @@ fn main() {
             }
         }
 
-        eprintln!(
+        debug!(
             "b:{}/x:{};y:{}/min_x:{};min_y:{}/max_x:{};max_y:{}",
             bomb_dir, bx, by, min_x, min_y, max_x, max_y
         );
@@ -95,6 +96,6 @@ fn main() {
         // To debug: eprintln!("Debug message...");
 
         // the location of the next window Batman should jump to.
-        println!("{} {}", x, y);
+        write_answer!("{} {}", x, y);
     }
 }
@@ -1,6 +1,10 @@
 // https://www.codingame.com/training/hard/the-labyrinth
 
-use std::io;
+use codinggame_backtocode_rust::params;
+use codinggame_backtocode_rust::timer::{self, TurnTimer};
+use codinggame_backtocode_rust::{debug, info, trace, transcript, warn, write_answer};
+use std::collections::VecDeque;
+use std::time::Duration;
 
 macro_rules! parse_input {
     ($x:expr, $t:ident) => {
@@ -8,6 +12,11 @@ macro_rules! parse_input {
     };
 }
 
+/// stepping on the command room triggers the alarm, while roaming we go around it when we can
+const COMMAND_ROOM_COST: i32 = 10;
+/// time kept to finish the turn once a search stops
+const TIME_MARGIN: Duration = Duration::from_millis(20);
+
 #[derive(Copy, Clone, Eq, Hash, PartialEq, Debug)]
 struct Point {
     x: i32,
@@ -19,14 +28,6 @@ impl Point {
         Self { x, y }
     }
 
-    pub fn get_squared_distance(&self, pos: &Point) -> i32 {
-        (pos.y - self.y).pow(2) + (pos.x - self.x).pow(2)
-    }
-
-    pub fn get_distance(&self, pos: &Point) -> f32 {
-        (self.get_squared_distance(pos) as f32).sqrt()
-    }
-
     pub fn manhattan_distance(&self, pos: &Point) -> i32 {
         let cost = 1;
         let dx = (self.x - pos.x).abs();
@@ -35,7 +36,7 @@ impl Point {
     }
 }
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 enum CellType {
     Empty,
     Wall,
@@ -70,25 +71,33 @@ impl CellType {
 struct Cell {
     pos: Point,
     cell_type: CellType,
+    /// overlay used to plan without exploring (return trip), the real cell type is kept untouched
+    assumed_wall: bool,
 }
 
 impl Cell {
     pub fn new(pos: Point) -> Self {
         Self {
             pos,
-            cell_type: CellType::Empty,
+            cell_type: CellType::Unknown,
+            assumed_wall: false,
         }
     }
 
-    pub fn reset(&mut self) {
-        self.cell_type = CellType::Empty;
-    }
-
     pub fn is_visitable(&self) -> bool {
-        self.cell_type != CellType::Wall
+        self.cell_type != CellType::Wall && !self.assumed_wall
     }
 }
 
+/// what happened to a cell when merging the last scan into the grid
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum MergeResult {
+    Unchanged,
+    Discovered,
+    /// the cell was known with another type, we keep the new one since the referee is the truth
+    Contradiction(CellType),
+}
+
 #[derive(Copy, Clone, Debug)]
 struct AStarPoint {
     point: Point,
@@ -107,11 +116,19 @@ impl PartialEq for AStarPoint {
     }
 }
 
+#[derive(Copy, Clone, Debug)]
+struct Visit {
+    /// number of steps from the bfs start
+    distance: i32,
+    parent: Option<Point>,
+}
+
 #[derive(Clone)]
 struct Grid {
     height: i32,
     width: i32,
     cells: Vec<Cell>,
+    command_room_cost: i32,
 }
 
 impl Grid {
@@ -125,27 +142,31 @@ impl Grid {
         Self {
             height: h,
             width: w,
-            cells: cells,
+            cells,
+            command_room_cost: params::get("command_room_cost", COMMAND_ROOM_COST),
         }
     }
 
-    pub fn set_cell_type(&mut self, pos: &Point, cell_type: CellType) {
-        let Some(cell) = self.get_mut_cell(pos) else {
-            return;
-        };
-        cell.cell_type = cell_type;
-    }
-
     pub fn get_cell_index(&self, pos: &Point) -> usize {
         (self.width * pos.y + pos.x) as usize
     }
 
+    pub fn is_in_bounds(&self, pos: &Point) -> bool {
+        (0..self.width).contains(&pos.x) && (0..self.height).contains(&pos.y)
+    }
+
     pub fn get_mut_cell(&mut self, pos: &Point) -> Option<&mut Cell> {
+        if !self.is_in_bounds(pos) {
+            return None;
+        }
         let index = self.get_cell_index(pos);
         self.cells.get_mut(index)
     }
 
     pub fn get_cell(&self, pos: &Point) -> Option<&Cell> {
+        if !self.is_in_bounds(pos) {
+            return None;
+        }
         let index = self.get_cell_index(pos);
         self.cells.get(index)
     }
@@ -154,7 +175,7 @@ impl Grid {
         if let Some(cell) = self.get_cell(pos) {
             return match cell.cell_type {
                 // if we can avoid clicking while we are roaming this is better
-                CellType::CommandRoom => 10,
+                CellType::CommandRoom => self.command_room_cost,
                 // a wall, should not happens but just to be sure we put a super high cost
                 CellType::Wall => i32::MAX,
                 _ => 1,
@@ -163,10 +184,6 @@ impl Grid {
         i32::MAX
     }
 
-    pub fn reset(&mut self) {
-        self.cells.clear();
-    }
-
     /// get the 4 directions neighbors, this function can return less than 4 points if cells are not visitables or do no exists
     pub fn get_neighbors_points(&self, pos: &Point) -> Vec<Point> {
         let mut neighbors = Vec::new();
@@ -190,7 +207,15 @@ impl Grid {
     ///
     /// documentation: http://theory.stanford.edu/~amitp/GameProgramming/ImplementationNotes.html
     /// documentation 2: https://www.geeksforgeeks.org/a-search-algorithm/
-    pub fn astar(&self, start: &Point, target: &Point, debug: bool) -> Vec<Point> {
+    ///
+    /// out of time, returns the path to the visited point the closest to the target
+    pub fn astar(
+        &self,
+        start: &Point,
+        target: &Point,
+        timer: &TurnTimer,
+        debug: bool,
+    ) -> Vec<Point> {
         let mut open = Vec::<AStarPoint>::new();
         let mut closed = Vec::<AStarPoint>::new();
         open.push(AStarPoint {
@@ -201,13 +226,22 @@ impl Grid {
             f: 0,
         });
 
-        while open.len() > 0 {
-            open.sort_by(|a, b| b.f.cmp(&a.f));
+        while !open.is_empty() {
+            if timer.should_stop(TIME_MARGIN) {
+                let closest = closed
+                    .iter()
+                    .filter(|p| p.parent.is_some())
+                    .min_by_key(|p| p.h)
+                    .copied();
+                warn!("a*: out of time, going toward {:?}", closest);
+                return closest.map_or_else(Vec::new, |p| Grid::get_astar_path(&closed, p, start));
+            }
+            open.sort_by_key(|p| std::cmp::Reverse(p.f));
             let q = open.pop().unwrap();
             closed.push(q);
 
             if debug {
-                eprintln!(
+                trace!(target: "astar",
                     "open: {:?}",
                     open.iter().map(|c| c.point).collect::<Vec<_>>()
                 );
@@ -218,32 +252,13 @@ impl Grid {
                     g: 0,
                     h: 0,
                     f: 0,
-                    parent: Some(q.point.clone()),
+                    parent: Some(q.point),
                     point: neighbor_point,
                 };
 
                 if &astar_neighbor.point == target {
-                    let mut path = Vec::new();
-                    let mut astar_point = astar_neighbor;
-                    path.push(*target);
-                    while let Some(parent_point) = astar_point.parent {
-                        let position = closed.iter().position(|p| p.point == parent_point).expect(
-                            format!(
-                                "WHAT ASTAR POINT NOT FOUND IN CLOSED QUEUE? {:?} | {:?}",
-                                parent_point, closed,
-                            )
-                            .as_str(),
-                        );
-                        astar_point = closed.get(position).unwrap().clone();
-                        path.push(astar_point.point);
-                    }
-                    if let Some(last) = path.pop() {
-                        if &last != start {
-                            path.push(last);
-                        }
-                    }
-                    path.reverse();
-                    eprintln!("a* path: {:?}", path);
+                    let path = Grid::get_astar_path(&closed, astar_neighbor, start);
+                    trace!(target: "astar", "a* path: {:?}", path);
                     return path;
                 }
 
@@ -270,16 +285,139 @@ impl Grid {
             }
         }
 
-        return Vec::new();
+        Vec::new()
+    }
+
+    /// follows the parents of the a* point back to start, start excluded
+    fn get_astar_path(closed: &[AStarPoint], last: AStarPoint, start: &Point) -> Vec<Point> {
+        let mut path = Vec::new();
+        let mut astar_point = last;
+        path.push(last.point);
+        while let Some(parent_point) = astar_point.parent {
+            let position = closed
+                .iter()
+                .position(|p| p.point == parent_point)
+                .unwrap_or_else(|| {
+                    panic!(
+                        "WHAT ASTAR POINT NOT FOUND IN CLOSED QUEUE? {:?} | {:?}",
+                        parent_point, closed
+                    )
+                });
+            astar_point = *closed.get(position).unwrap();
+            path.push(astar_point.point);
+        }
+        if let Some(last) = path.pop() {
+            if &last != start {
+                path.push(last);
+            }
+        }
+        path.reverse();
+        path
+    }
+
+    /// returns how far every cell is from the given position, walking only over known visitable cells
+    /// the vec is indexed like `cells`, unreachable cells are `None`
+    pub fn bfs(&self, start: &Point) -> Vec<Option<Visit>> {
+        let mut visits = vec![None; self.cells.len()];
+        let mut queue = VecDeque::new();
+        visits[self.get_cell_index(start)] = Some(Visit {
+            distance: 0,
+            parent: None,
+        });
+        queue.push_back(*start);
+
+        while let Some(pos) = queue.pop_front() {
+            let distance = visits[self.get_cell_index(&pos)].unwrap().distance;
+            for neighbor_point in self.get_neighbors_points(&pos) {
+                let index = self.get_cell_index(&neighbor_point);
+                if visits[index].is_some() || self.cells[index].cell_type == CellType::Unknown {
+                    continue;
+                }
+                visits[index] = Some(Visit {
+                    distance: distance + 1,
+                    parent: Some(pos),
+                });
+                queue.push_back(neighbor_point);
+            }
+        }
+
+        visits
+    }
+
+    /// follows the bfs parents back from the target and returns the first point to go to from start
+    pub fn get_first_step(
+        visits: &[Option<Visit>],
+        grid: &Grid,
+        start: &Point,
+        target: &Point,
+    ) -> Option<Point> {
+        let mut point = *target;
+        loop {
+            let parent = visits.get(grid.get_cell_index(&point))?.as_ref()?.parent?;
+            if &parent == start {
+                return Some(point);
+            }
+            point = parent;
+        }
+    }
+
+    /// number of `?` cells Kirk would reveal if he was standing at the given position (he scans 5x5 around him)
+    pub fn count_unknown_around(&self, pos: &Point) -> i32 {
+        let mut count = 0;
+        for y in (pos.y - 2)..=(pos.y + 2) {
+            for x in (pos.x - 2)..=(pos.x + 2) {
+                if let Some(cell) = self.get_cell(&Point::new(x, y)) {
+                    if cell.cell_type == CellType::Unknown {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    /// merges a scanned cell into the grid, only `?` cells can be upgraded, a scanned `?` never erases what we know
+    pub fn merge_cell_type(&mut self, pos: &Point, cell_type: CellType) -> MergeResult {
+        let Some(cell) = self.get_mut_cell(pos) else {
+            return MergeResult::Unchanged;
+        };
+        if cell_type == CellType::Unknown || cell.cell_type == cell_type {
+            return MergeResult::Unchanged;
+        }
+        let previous = cell.cell_type;
+        cell.cell_type = cell_type;
+        cell.assumed_wall = false;
+        if previous == CellType::Unknown {
+            return MergeResult::Discovered;
+        }
+        MergeResult::Contradiction(previous)
     }
 
-    fn set_unknown_as_wall(&mut self) {
+    /// set the assumed wall overlay on every `?` cell, they are still `?` in the grid
+    fn assume_unknown_as_wall(&mut self) {
         for cell in &mut self.cells {
             if cell.cell_type == CellType::Unknown {
-                cell.cell_type = CellType::Wall;
+                cell.assumed_wall = true;
             }
         }
     }
+
+    fn assume_wall(&mut self, pos: &Point) {
+        if let Some(cell) = self.get_mut_cell(pos) {
+            cell.assumed_wall = true;
+        }
+    }
+
+    pub fn known_count(&self) -> usize {
+        self.cells.len() - self.unknown_count()
+    }
+
+    pub fn unknown_count(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|cell| cell.cell_type == CellType::Unknown)
+            .count()
+    }
 }
 
 impl std::fmt::Debug for Grid {
@@ -307,7 +445,11 @@ struct Game {
     command_pos: Option<Point>,
     start_pos: Point,
     hit_command: bool,
-    roam_to: Option<Point>,
+    /// cells discovered during the last scan
+    discovered_count: i32,
+    contradictions_count: i32,
+    /// the time left this turn
+    timer: TurnTimer,
 }
 
 impl Game {
@@ -320,12 +462,15 @@ impl Game {
             command_pos: None,
             start_pos: Point::new(0, 0),
             hit_command: false,
-            roam_to: None,
+            discovered_count: 0,
+            contradictions_count: 0,
+            timer: TurnTimer::unlimited(),
         }
     }
 
     pub fn set_player_pos(&mut self, player_pos: Point) {
         self.round += 1;
+        self.discovered_count = 0;
         self.player_pos = player_pos;
         if let Some(command_pos) = self.command_pos {
             if self.player_pos == command_pos {
@@ -338,7 +483,17 @@ impl Game {
         for (x, c) in row.chars().enumerate() {
             let cell_type = CellType::from_char(c);
             let pos = &Point::new(x as i32, y);
-            self.grid.set_cell_type(pos, cell_type.clone());
+            match self.grid.merge_cell_type(pos, cell_type) {
+                MergeResult::Discovered => self.discovered_count += 1,
+                MergeResult::Contradiction(previous) => {
+                    self.contradictions_count += 1;
+                    debug!(
+                        "CONTRADICTION at {:?}: was {:?}, now {:?}",
+                        pos, previous, cell_type
+                    );
+                }
+                MergeResult::Unchanged => {}
+            }
             match cell_type {
                 CellType::CommandRoom => self.command_pos = Some(*pos),
                 CellType::Start => self.start_pos = *pos,
@@ -347,69 +502,126 @@ impl Game {
         }
     }
 
+    /// returns the number of rounds needed to go from the command room back to the starting position
+    /// using only the cells we already know (`?` are considered as walls), `None` if there is no such path yet
+    pub fn get_return_path_length(&self) -> Option<i32> {
+        let command_pos = self.command_pos?;
+        let mut grid = self.grid.clone();
+        grid.assume_unknown_as_wall();
+        let path = grid.astar(&command_pos, &self.start_pos, &self.timer, false);
+        // out of time the path stops on the way, we do not know the length yet
+        if path.last() != Some(&self.start_pos) {
+            return None;
+        }
+        Some(path.len() as i32)
+    }
+
+    /// we can trigger the alarm only if we are sure to be back before it goes off
+    pub fn can_trigger_alarm(&self) -> bool {
+        self.get_return_path_length()
+            .is_some_and(|length| length <= self.alarm)
+    }
+
+    /// the grid used while roaming, the command room is a wall here so we never trigger the alarm by mistake
+    fn get_roaming_grid(&self) -> Grid {
+        let mut grid = self.grid.clone();
+        if let Some(command_pos) = &self.command_pos {
+            grid.assume_wall(command_pos);
+        }
+        grid
+    }
+
+    /// returns the first step toward the frontier cell with the best revealed `?` per step ratio
+    fn get_best_frontier_step(&self, grid: &Grid) -> Option<Point> {
+        let visits = grid.bfs(&self.player_pos);
+        let mut best: Option<(f32, Point)> = None;
+        for cell in &grid.cells {
+            let Some(Some(visit)) = visits.get(grid.get_cell_index(&cell.pos)) else {
+                continue;
+            };
+            let gain = grid.count_unknown_around(&cell.pos);
+            if gain == 0 {
+                continue;
+            }
+            let detour = match self.command_pos {
+                Some(command_pos) => {
+                    command_pos.manhattan_distance(&cell.pos)
+                        + cell.pos.manhattan_distance(&self.start_pos)
+                        - command_pos.manhattan_distance(&self.start_pos)
+                }
+                None => 0,
+            };
+            let score = gain as f32 / (visit.distance + detour).max(1) as f32;
+            match best {
+                Some((best_score, _)) if best_score >= score => {}
+                _ => best = Some((score, cell.pos)),
+            }
+        }
+
+        let (score, target) = best?;
+        debug!("best frontier cell: {:?} (score: {})", target, score);
+        Grid::get_first_step(&visits, grid, &self.player_pos, &target)
+    }
+
     pub fn get_next_target_point(&mut self) -> Option<Point> {
         // hit command, this is retrieve mode, just find the fatest path using a*
         if self.hit_command {
-            eprintln!("--MODE: RETURN TO STARTING POSITION: {:?}", self.start_pos);
-            // set all unknown cells as walls to avoid discovering new path and wait time
-            self.grid.set_unknown_as_wall();
+            info!("--MODE: RETURN TO STARTING POSITION: {:?}", self.start_pos);
+            // assume all unknown cells are walls to avoid discovering new path and wait time
+            self.grid.assume_unknown_as_wall();
 
             return self
                 .grid
-                .astar(&self.player_pos, &self.start_pos, false)
-                .get(0)
+                .astar(&self.player_pos, &self.start_pos, &self.timer, false)
+                .first()
                 .copied()
-                .or_else(|| Some(self.start_pos));
+                .or(Some(self.start_pos));
         }
 
-        // roaming mode
-        // we are looking for every ? - if there is at least one targetable
-        // from the closest to the player to the furthest
-        if let Some(roam_to) = self.roam_to {
-            if let Some(point) = self.grid.astar(&self.player_pos, &roam_to, false).get(0) {
-                if point != &self.player_pos
-                // makes sure the target cell (roam_to) is not discovered while we moved to it
-                    && self.grid.get_cell(&roam_to).unwrap().cell_type == CellType::Unknown
+        // we know a path back that is short enough, go to the command room
+        if let Some(command_pos) = &self.command_pos {
+            if self.can_trigger_alarm() {
+                info!(
+                    "--MODE: GO TO COMMAND STATION: {:?} (return in {:?} rounds, alarm: {})",
+                    command_pos,
+                    self.get_return_path_length(),
+                    self.alarm
+                );
+                if let Some(point) = self
+                    .grid
+                    .astar(&self.player_pos, command_pos, &self.timer, false)
+                    .first()
                 {
-                    eprintln!(">> STRAIGHT TO: {:?}", roam_to);
-                    return Some(point.clone());
+                    return Some(*point);
                 }
             }
         }
-        let mut unknown_cells = self
-            .grid
-            .cells
-            .iter()
-            .filter(|cell| cell.cell_type == CellType::Unknown)
-            .collect::<Vec<_>>();
-        unknown_cells.sort_by(|&a, &b| {
-            b.pos
-                .manhattan_distance(&self.player_pos)
-                .cmp(&a.pos.manhattan_distance(&self.player_pos))
-        });
-        let mut tries = 0;
-        while let Some(cell) = unknown_cells.pop() {
-            tries += 1;
-            if tries > 4 {
-                break;
-            }
-            eprintln!("closest UNKNOWN cell to roam: {:?}", cell.pos);
-            if let Some(point) = self.grid.astar(&self.player_pos, &cell.pos, false).get(0) {
-                eprintln!(">> NEXT TARGET: {:?}", point);
-                self.roam_to = Some(cell.pos);
-                return Some(point.clone());
-            }
+
+        // roaming mode
+        // one bfs from the player over the known cells, then go toward the frontier cell
+        // revealing the more ? per step travelled
+        // once the command room is known, the frontier cells away from the command room -> start
+        // line cost more since they are not likely to shorten the return path
+        let grid = self.get_roaming_grid();
+        if let Some(point) = self.get_best_frontier_step(&grid) {
+            debug!(">> NEXT TARGET: {:?}", point);
+            return Some(point);
         }
 
         // nothing more to roam, we should have found the command
-        // just press it
+        // the return path is not short enough but this is the best we know, just press it
         // we have found the command pos, just use a* to go there
         if let Some(command_pos) = &self.command_pos {
-            eprintln!("--MODE: GO TO COMMAND STATION: {:?}", command_pos);
+            info!(
+                "--MODE: GO TO COMMAND STATION ANYWAY: {:?} (return in {:?} rounds, alarm: {})",
+                command_pos,
+                self.get_return_path_length(),
+                self.alarm
+            );
             return self
                 .grid
-                .astar(&self.player_pos, command_pos, false)
-                .get(0)
+                .astar(&self.player_pos, command_pos, &self.timer, false)
+                .first()
                 .copied();
         }
 
@@ -424,7 +636,7 @@ impl Game {
 fn main() {
     // init game
     let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
+    transcript::read_line(&mut input_line);
     let inputs = input_line.split(" ").collect::<Vec<_>>();
     let r = parse_input!(inputs[0], i32); // number of rows.
     let c = parse_input!(inputs[1], i32); // number of columns.
@@ -434,7 +646,7 @@ fn main() {
     // game loop
     loop {
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         let inputs = input_line.split(" ").collect::<Vec<_>>();
         let kr = parse_input!(inputs[0], i32); // row where Rick is located.
         let kc = parse_input!(inputs[1], i32); // column where Rick is located.
@@ -442,31 +654,319 @@ fn main() {
 
         for y in 0..r as usize {
             let mut input_line = String::new();
-            io::stdin().read_line(&mut input_line).unwrap();
+            transcript::read_line(&mut input_line);
             let row = input_line.trim().to_string(); // C of the characters in '#.TC?' (i.e. one line of the ASCII maze).
             game.decode_row(y as i32, row);
         }
+        game.timer = TurnTimer::for_turn(timer::THE_LABYRINTH, game.round);
 
-        eprintln!("{:?}", game.grid);
+        trace!(target: "grid", "{:?}", game.grid);
+        debug!(
+            "known: {} / unknown: {} (discovered: {}, contradictions: {})",
+            game.grid.known_count(),
+            game.grid.unknown_count(),
+            game.discovered_count,
+            game.contradictions_count
+        );
 
         if let Some(next_point) = game.get_next_target_point() {
-            eprintln!("next_point: {:?}", next_point);
+            debug!("next_point: {:?}", next_point);
             if next_point.x == game.player_pos.x {
                 if next_point.y <= game.player_pos.y {
-                    println!("UP");
+                    write_answer!("UP");
                 } else {
-                    println!("DOWN");
+                    write_answer!("DOWN");
                 }
             } else {
                 if next_point.x <= game.player_pos.x {
-                    println!("LEFT");
+                    write_answer!("LEFT");
                 } else {
-                    println!("RIGHT");
+                    write_answer!("RIGHT");
                 }
             }
         } else {
-            eprintln!("WHAT I DUNNO WHAT TO DO");
-            println!("RIGHT"); // Rick's next move (UP DOWN LEFT or RIGHT).
+            warn!("WHAT I DUNNO WHAT TO DO");
+            write_answer!("RIGHT"); // Rick's next move (UP DOWN LEFT or RIGHT).
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codinggame_backtocode_rust::maze;
+    use codinggame_backtocode_rust::random::Random;
+
+    const FUEL: i32 = 1200;
+    const MAZE_COUNT: u64 = 40;
+
+    /// plays the maze like the puzzle does (Kirk scans the 5x5 cells around him), the moves used to be back at
+    /// the start, `None` if Kirk hit a wall, ran out of fuel or the alarm went off
+    fn play(
+        maze: &[Vec<char>],
+        alarm: i32,
+        mut get_step: impl FnMut(&mut Game) -> Option<Point>,
+    ) -> Option<i32> {
+        let (rows, columns) = (maze.len() as i32, maze[0].len() as i32);
+        let start = (0..rows)
+            .flat_map(|y| (0..columns).map(move |x| Point::new(x, y)))
+            .find(|pos| maze[pos.y as usize][pos.x as usize] == 'T')
+            .unwrap();
+        let mut game = Game::new(columns, rows, alarm);
+        let mut seen = vec![vec![false; columns as usize]; rows as usize];
+        let mut pos = start;
+        let mut alarm_left = None;
+        for turn in 1..=FUEL {
+            for y in (pos.y - 2).max(0)..=(pos.y + 2).min(rows - 1) {
+                for x in (pos.x - 2).max(0)..=(pos.x + 2).min(columns - 1) {
+                    seen[y as usize][x as usize] = true;
+                }
+            }
+            game.set_player_pos(pos);
+            for (y, (row, seen_row)) in maze.iter().zip(seen.iter()).enumerate() {
+                let row = row
+                    .iter()
+                    .zip(seen_row)
+                    .map(|(cell, seen)| if *seen { *cell } else { '?' })
+                    .collect();
+                game.decode_row(y as i32, row);
+            }
+
+            let step = get_step(&mut game)?;
+            if step.manhattan_distance(&pos) != 1 || maze[step.y as usize][step.x as usize] == '#' {
+                return None;
+            }
+            pos = step;
+            match alarm_left.as_mut() {
+                Some(left) => {
+                    *left -= 1;
+                    if pos == start {
+                        return Some(turn);
+                    }
+                    if *left <= 0 {
+                        return None;
+                    }
+                }
+                None if maze[pos.y as usize][pos.x as usize] == 'C' => alarm_left = Some(alarm),
+                None => {}
+            }
+        }
+        None
+    }
+
+    /// the exploration before the frontier cells: a* toward the first of the 4 closest `?` cells it can reach
+    fn get_closest_unknown_step(game: &mut Game, roam_to: &mut Option<Point>) -> Option<Point> {
+        if game.hit_command || game.can_trigger_alarm() {
+            return game.get_next_target_point();
+        }
+        let grid = game.get_roaming_grid();
+        if let Some(target) = *roam_to {
+            if let Some(point) = grid
+                .astar(&game.player_pos, &target, &game.timer, false)
+                .first()
+            {
+                if point != &game.player_pos
+                    && game.grid.get_cell(&target).unwrap().cell_type == CellType::Unknown
+                {
+                    return Some(*point);
+                }
+            }
+        }
+        let mut unknown_cells = game
+            .grid
+            .cells
+            .iter()
+            .filter(|cell| cell.cell_type == CellType::Unknown)
+            .collect::<Vec<_>>();
+        let get_roaming_cost = |pos: &Point| match game.command_pos {
+            Some(command_pos) => (
+                command_pos.manhattan_distance(pos) + pos.manhattan_distance(&game.start_pos),
+                pos.manhattan_distance(&game.player_pos),
+            ),
+            None => (pos.manhattan_distance(&game.player_pos), 0),
+        };
+        unknown_cells.sort_by_key(|cell| std::cmp::Reverse(get_roaming_cost(&cell.pos)));
+        for cell in unknown_cells.iter().rev().take(4) {
+            if let Some(point) = grid
+                .astar(&game.player_pos, &cell.pos, &game.timer, false)
+                .first()
+            {
+                *roam_to = Some(cell.pos);
+                return Some(*point);
+            }
+        }
+        game.get_next_target_point()
+    }
+
+    /// the maze from its rows
+    fn parse_maze(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    /// the game once Kirk scanned the 5x5 cells around his position in the maze
+    fn scan(maze: &[Vec<char>], kirk: Point, alarm: i32) -> Game {
+        let (rows, columns) = (maze.len() as i32, maze[0].len() as i32);
+        let mut game = Game::new(columns, rows, alarm);
+        game.set_player_pos(kirk);
+        for (y, row) in maze.iter().enumerate() {
+            let row = row
+                .iter()
+                .enumerate()
+                .map(|(x, cell)| {
+                    let pos = Point::new(x as i32, y as i32);
+                    if (pos.x - kirk.x).abs() <= 2 && (pos.y - kirk.y).abs() <= 2 {
+                        *cell
+                    } else {
+                        '?'
+                    }
+                })
+                .collect();
+            game.decode_row(y as i32, row);
+        }
+        game
+    }
+
+    #[test]
+    fn the_frontier_step_leaves_the_scanned_end_of_a_corridor() {
+        // the left end of the corridor is already scanned, a step left reveals nothing
+        let maze = parse_maze(&["###########", "#.T.......#", "###########"]);
+        let mut game = scan(&maze, Point::new(2, 1), 20);
+        assert_eq!(
+            game.get_best_frontier_step(&game.get_roaming_grid()),
+            Some(Point::new(3, 1))
+        );
+        assert_eq!(game.get_next_target_point(), Some(Point::new(3, 1)));
+    }
+
+    #[test]
+    fn the_frontier_cells_are_reached_through_known_cells() {
+        // the `?` cells right of Kirk are the closest ones but behind a wall, the way there goes down first
+        let maze = parse_maze(&[
+            "#########",
+            "#T#.....#",
+            "#.#.....#",
+            "#.#.....#",
+            "#.......#",
+            "#########",
+        ]);
+        let mut game = scan(&maze, Point::new(1, 1), 30);
+        assert_eq!(game.get_next_target_point(), Some(Point::new(1, 2)));
+    }
+
+    #[test]
+    fn a_corridor_is_explored_without_turning_back() {
+        let maze = parse_maze(&["################", "#T............C#", "################"]);
+        let moves = play(&maze, 13, |game| game.get_next_target_point());
+        assert_eq!(moves, Some(2 * 13));
+    }
+
+    #[test]
+    fn a_scanned_dead_end_is_not_visited() {
+        // the dead end above the start is scanned from the start, Kirk goes straight to the command room and back
+        let maze = parse_maze(&["##########", "#.########", "#T......C#", "##########"]);
+        let moves = play(&maze, 7, |game| game.get_next_target_point());
+        assert_eq!(moves, Some(2 * 7));
+    }
+
+    #[test]
+    fn the_alarm_can_be_exactly_the_way_back() {
+        // the only way from the start to the command room is 24 steps, the alarm leaves no spare round
+        let maze = parse_maze(&[
+            "##############",
+            "#T...........#",
+            "############.#",
+            "#C...........#",
+            "##############",
+        ]);
+        let moves = play(&maze, 24, |game| game.get_next_target_point());
+        assert_eq!(moves, Some(2 * 24));
+    }
+
+    #[test]
+    fn a_scanned_unknown_cell_is_discovered() {
+        let mut grid = Grid::new(3, 1);
+        let pos = Point::new(1, 0);
+        assert_eq!(
+            grid.merge_cell_type(&pos, CellType::Empty),
+            MergeResult::Discovered
+        );
+        assert_eq!(
+            grid.merge_cell_type(&pos, CellType::Empty),
+            MergeResult::Unchanged
+        );
+        // a `?` in a later scan does not erase the cell
+        assert_eq!(
+            grid.merge_cell_type(&pos, CellType::Unknown),
+            MergeResult::Unchanged
+        );
+        assert_eq!(grid.get_cell(&pos).unwrap().cell_type, CellType::Empty);
+        assert_eq!((grid.known_count(), grid.unknown_count()), (1, 2));
+    }
+
+    #[test]
+    fn a_contradiction_keeps_the_last_scan() {
+        let mut grid = Grid::new(3, 1);
+        let pos = Point::new(1, 0);
+        grid.merge_cell_type(&pos, CellType::Empty);
+        assert_eq!(
+            grid.merge_cell_type(&pos, CellType::Wall),
+            MergeResult::Contradiction(CellType::Empty)
+        );
+        assert_eq!(grid.get_cell(&pos).unwrap().cell_type, CellType::Wall);
+
+        let mut game = Game::new(3, 1, 10);
+        game.set_player_pos(Point::new(0, 0));
+        game.decode_row(0, "T.?".to_string());
+        assert_eq!((game.discovered_count, game.contradictions_count), (2, 0));
+        game.set_player_pos(Point::new(0, 0));
+        game.decode_row(0, "T#.".to_string());
+        assert_eq!((game.discovered_count, game.contradictions_count), (1, 1));
+        assert_eq!(format!("{:?}", game.grid), "T#.\n");
+    }
+
+    #[test]
+    fn an_assumed_wall_is_cleared_once_the_cell_is_seen() {
+        let mut grid = Grid::new(3, 1);
+        grid.merge_cell_type(&Point::new(0, 0), CellType::Start);
+        grid.assume_unknown_as_wall();
+        let pos = Point::new(1, 0);
+        let cell = grid.get_cell(&pos).unwrap();
+        // the overlay keeps the cell unknown in the map
+        assert_eq!(cell.cell_type, CellType::Unknown);
+        assert!(!cell.is_visitable());
+
+        assert_eq!(
+            grid.merge_cell_type(&pos, CellType::Empty),
+            MergeResult::Discovered
+        );
+        assert!(grid.get_cell(&pos).unwrap().is_visitable());
+        assert!(!grid.get_cell(&Point::new(2, 0)).unwrap().is_visitable());
+    }
+
+    /// compares the moves on generated mazes with the strategy before the frontier cells, run with
+    /// `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn frontier_exploration_uses_less_fuel_than_closest_unknown_cells() {
+        let mut random = Random::new(27);
+        let (mut frontier_moves, mut closest_moves) = (0, 0);
+        for index in 0..MAZE_COUNT {
+            let (maze, alarm) = maze::generate(&mut random, 15, 30);
+            let frontier = play(&maze, alarm, |game| game.get_next_target_point());
+            let mut roam_to = None;
+            let closest = play(&maze, alarm, |game| {
+                get_closest_unknown_step(game, &mut roam_to)
+            });
+            let frontier = frontier.unwrap_or_else(|| panic!("maze {} not solved", index));
+            frontier_moves += frontier;
+            closest_moves += closest.unwrap_or(FUEL);
         }
+        assert!(
+            frontier_moves < closest_moves,
+            "{} mazes: {} moves toward frontier cells, {} toward the closest unknown cells",
+            MAZE_COUNT,
+            frontier_moves,
+            closest_moves
+        );
     }
 }
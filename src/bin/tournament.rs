@@ -0,0 +1,398 @@
+// plays bots against each other with a local referee and rates them, to compare builds or parameter sets offline
+//
+// usage: tournament [--game back-to-the-code|code-a-la-mode|code-vs-zombies] [--matches N] [--threads N] [--seed N]
+//                   [--partner BOT] [--referee-args ARGS] <bot> <bot> [<bot>...]
+//
+// every pair of bots plays `--matches` matches (seeds `--seed`, `--seed + 1`...), a bot is a command (quoted if it has
+// arguments) or a scripted bot of the referee (`scripted:random`), the referees are looked for next to this binary:
+// - back-to-the-code: a 2 players game, the bots swap sides every other seed, the most cells wins
+// - code-a-la-mode: both bots cook the same customers with the same `--partner` (`scripted:idle` by default),
+//   the best total wins
+// - code-vs-zombies: both bots play the same map, the best score wins
+//
+// the ratings are Elo points fitted on every result (Bradley-Terry, a draw is half a win), with a 95% interval,
+// the bots are rated 1500 on average
+//
+// a build against the one before a change:
+// cp target/release/code-a-la-mode /tmp/code-a-la-mode-before
+// tournament --matches 20 target/release/code-a-la-mode /tmp/code-a-la-mode-before
+
+use codinggame_backtocode_rust::referee::{self, GameKind};
+use std::collections::VecDeque;
+use std::env;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+/// a draw against every opponent is added to the results, a bot winning every match keeps a finite rating
+const PRIOR_DRAWS: f64 = 1.;
+const BRADLEY_TERRY_ITERATIONS: usize = 500;
+const AVERAGE_RATING: f64 = 1500.;
+
+struct Settings {
+    game: GameKind,
+    referee: PathBuf,
+    referee_args: Vec<String>,
+    partner: String,
+    bots: Vec<String>,
+}
+
+/// a match between two bots
+#[derive(Copy, Clone)]
+struct Match {
+    first: usize,
+    second: usize,
+    seed: u64,
+}
+
+/// the scores of both bots, `None` when the referee failed
+struct MatchResult {
+    game: Match,
+    scores: Option<(i64, i64)>,
+}
+
+/// runs the referee and reads the score of each player from its output
+fn run_referee(settings: &Settings, seed: u64, bots: &[&str]) -> Option<Vec<i64>> {
+    let mut args = vec!["--seed".to_string(), seed.to_string()];
+    args.extend(settings.referee_args.iter().cloned());
+    args.extend(bots.iter().map(|bot| bot.to_string()));
+    referee::run_referee(settings.game, &settings.referee, &args)
+}
+
+fn play(settings: &Settings, game: Match) -> Option<(i64, i64)> {
+    let (first, second) = (&settings.bots[game.first], &settings.bots[game.second]);
+    match settings.game {
+        GameKind::BackToTheCode => {
+            // the start positions depend on the side, both bots play each side
+            if game.seed.is_multiple_of(2) {
+                let scores = run_referee(settings, game.seed, &[first, second])?;
+                Some((*scores.first()?, *scores.get(1)?))
+            } else {
+                let scores = run_referee(settings, game.seed, &[second, first])?;
+                Some((*scores.get(1)?, *scores.first()?))
+            }
+        }
+        GameKind::CodeALaMode => {
+            let first_score = run_referee(settings, game.seed, &[first, &settings.partner])?;
+            let second_score = run_referee(settings, game.seed, &[second, &settings.partner])?;
+            Some((*first_score.first()?, *second_score.first()?))
+        }
+        GameKind::CodeVsZombies => {
+            let first_score = run_referee(settings, game.seed, &[first])?;
+            let second_score = run_referee(settings, game.seed, &[second])?;
+            Some((*first_score.first()?, *second_score.first()?))
+        }
+    }
+}
+
+/// plays the matches on `threads` threads, the results come back in the order of the matches
+fn play_all(settings: Arc<Settings>, matches: Vec<Match>, threads: usize) -> Vec<MatchResult> {
+    let count = matches.len();
+    let queue = Arc::new(Mutex::new(
+        matches.into_iter().enumerate().collect::<VecDeque<_>>(),
+    ));
+    let (sender, receiver) = mpsc::channel();
+    let workers = (0..threads.max(1))
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let settings = Arc::clone(&settings);
+            let sender = sender.clone();
+            thread::spawn(move || loop {
+                let Some((index, game)) = queue.lock().unwrap().pop_front() else {
+                    return;
+                };
+                let scores = play(&settings, game);
+                let _ = sender.send((index, MatchResult { game, scores }));
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(sender);
+
+    let mut results = Vec::with_capacity(count);
+    for (index, result) in receiver {
+        if let Some((first, second)) = result.scores {
+            eprintln!(
+                "match {}/{} seed {}: {} {} - {} {}",
+                results.len() + 1,
+                count,
+                result.game.seed,
+                result.game.first,
+                first,
+                second,
+                result.game.second
+            );
+        } else {
+            eprintln!("match seed {}: the referee failed", result.game.seed);
+        }
+        results.push((index, result));
+    }
+    for worker in workers {
+        let _ = worker.join();
+    }
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// the wins (a draw is half a win) and games of every bot against every other one
+struct Standings {
+    wins: Vec<Vec<f64>>,
+    games: Vec<Vec<f64>>,
+}
+
+impl Standings {
+    pub fn new(bot_count: usize, results: &[MatchResult]) -> Self {
+        let mut standings = Self {
+            wins: vec![vec![0.; bot_count]; bot_count],
+            games: vec![vec![0.; bot_count]; bot_count],
+        };
+        for result in results {
+            let Some((first, second)) = result.scores else {
+                continue;
+            };
+            let (a, b) = (result.game.first, result.game.second);
+            let points = match first.cmp(&second) {
+                std::cmp::Ordering::Greater => 1.,
+                std::cmp::Ordering::Equal => 0.5,
+                std::cmp::Ordering::Less => 0.,
+            };
+            standings.wins[a][b] += points;
+            standings.wins[b][a] += 1. - points;
+            standings.games[a][b] += 1.;
+            standings.games[b][a] += 1.;
+        }
+        standings
+    }
+
+    /// the share of points of the bot over all its games
+    pub fn get_win_rate(&self, bot: usize) -> f64 {
+        let games = self.games[bot].iter().sum::<f64>();
+        if games == 0. {
+            return 0.;
+        }
+        self.wins[bot].iter().sum::<f64>() / games
+    }
+
+    /// the Elo rating of every bot and the half width of its 95% interval
+    pub fn get_ratings(&self) -> Vec<(f64, f64)> {
+        let count = self.wins.len();
+        let wins = |i: usize, j: usize| self.wins[i][j] + PRIOR_DRAWS / 2.;
+        let games = |i: usize, j: usize| self.games[i][j] + PRIOR_DRAWS;
+
+        // minorization-maximization of the Bradley-Terry likelihood
+        let mut strengths = vec![1.; count];
+        for _ in 0..BRADLEY_TERRY_ITERATIONS {
+            for i in 0..count {
+                let total_wins = (0..count)
+                    .filter(|j| *j != i)
+                    .map(|j| wins(i, j))
+                    .sum::<f64>();
+                let denominator = (0..count)
+                    .filter(|j| *j != i)
+                    .map(|j| games(i, j) / (strengths[i] + strengths[j]))
+                    .sum::<f64>();
+                if denominator > 0. {
+                    strengths[i] = total_wins / denominator;
+                }
+            }
+            let log_mean = strengths.iter().map(|s: &f64| s.ln()).sum::<f64>() / count as f64;
+            for strength in strengths.iter_mut() {
+                *strength /= log_mean.exp();
+            }
+        }
+
+        let elo_per_log = 400. / std::f64::consts::LN_10;
+        (0..count)
+            .map(|i| {
+                let information = (0..count)
+                    .filter(|j| *j != i)
+                    .map(|j| {
+                        let p = strengths[i] / (strengths[i] + strengths[j]);
+                        self.games[i][j] * p * (1. - p)
+                    })
+                    .sum::<f64>();
+                let interval = if information > 0. {
+                    1.96 * elo_per_log / information.sqrt()
+                } else {
+                    f64::INFINITY
+                };
+                (AVERAGE_RATING + elo_per_log * strengths[i].ln(), interval)
+            })
+            .collect()
+    }
+}
+
+fn main() {
+    let mut game = GameKind::CodeALaMode;
+    let mut matches = 10;
+    let mut threads = thread::available_parallelism().map_or(1, |count| count.get());
+    let mut seed = 1;
+    let mut partner = "scripted:idle".to_string();
+    let mut referee_args = Vec::new();
+    let mut bots = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--game" => {
+                let name = args.next().expect("--game needs a name");
+                game = GameKind::parse(&name).unwrap_or_else(|| panic!("unknown game {}", name));
+            }
+            "--matches" => {
+                matches = args
+                    .next()
+                    .expect("--matches needs a value")
+                    .parse()
+                    .unwrap()
+            }
+            "--threads" => {
+                threads = args
+                    .next()
+                    .expect("--threads needs a value")
+                    .parse()
+                    .unwrap()
+            }
+            "--seed" => seed = args.next().expect("--seed needs a value").parse().unwrap(),
+            "--partner" => partner = args.next().expect("--partner needs a bot"),
+            "--referee-args" => {
+                referee_args = args
+                    .next()
+                    .expect("--referee-args needs a value")
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect()
+            }
+            _ => bots.push(arg),
+        }
+    }
+    if bots.len() < 2 {
+        eprintln!("usage: tournament [--game back-to-the-code|code-a-la-mode|code-vs-zombies] [--matches N] [--threads N] [--seed N] [--partner BOT] [--referee-args ARGS] <bot> <bot> [<bot>...]");
+        std::process::exit(2);
+    }
+
+    let mut schedule = Vec::new();
+    for first in 0..bots.len() {
+        for second in first + 1..bots.len() {
+            for index in 0..matches {
+                schedule.push(Match {
+                    first,
+                    second,
+                    seed: seed + index,
+                });
+            }
+        }
+    }
+    let settings = Arc::new(Settings {
+        game,
+        referee: referee::find_bin(game.get_referee()),
+        referee_args,
+        partner,
+        bots,
+    });
+    let results = play_all(Arc::clone(&settings), schedule, threads);
+
+    let errors = results
+        .iter()
+        .filter(|result| result.scores.is_none())
+        .count();
+    println!("{} matches, {} failed", results.len(), errors);
+    let standings = Standings::new(settings.bots.len(), &results);
+    for first in 0..settings.bots.len() {
+        for second in first + 1..settings.bots.len() {
+            let games = standings.games[first][second];
+            if games == 0. {
+                continue;
+            }
+            println!(
+                "{} vs {}: {:.1} - {:.1} ({:.0}%)",
+                first,
+                second,
+                standings.wins[first][second],
+                standings.wins[second][first],
+                100. * standings.wins[first][second] / games
+            );
+        }
+    }
+
+    let ratings = standings.get_ratings();
+    let mut order = (0..settings.bots.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| ratings[*b].0.total_cmp(&ratings[*a].0));
+    for bot in order {
+        println!(
+            "{}: {:.0} ± {:.0}, win rate {:.0}% ({})",
+            bot,
+            ratings[bot].0,
+            ratings[bot].1,
+            100. * standings.get_win_rate(bot),
+            settings.bots[bot]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// matches between the bots 0 and 1 ending with these scores
+    fn get_results(scores: &[(i64, i64)]) -> Vec<MatchResult> {
+        scores
+            .iter()
+            .enumerate()
+            .map(|(index, scores)| MatchResult {
+                game: Match {
+                    first: 0,
+                    second: 1,
+                    seed: index as u64,
+                },
+                scores: Some(*scores),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn equal_bots_are_rated_the_average() {
+        let standings = Standings::new(2, &get_results(&[(3, 1), (1, 3), (2, 0), (0, 2)]));
+        let ratings = standings.get_ratings();
+        for (rating, interval) in &ratings {
+            assert!((rating - AVERAGE_RATING).abs() < 1e-6, "{:?}", ratings);
+            assert!(interval.is_finite() && *interval > 0.);
+        }
+        assert_eq!(standings.get_win_rate(0), 0.5);
+    }
+
+    #[test]
+    fn a_dominant_bot_is_rated_higher() {
+        let mut scores = vec![(2, 1); 9];
+        scores.push((1, 2));
+        let standings = Standings::new(2, &get_results(&scores));
+        let ratings = standings.get_ratings();
+        assert!(ratings[0].0 > ratings[1].0 + 200., "{:?}", ratings);
+        // the ratings stay around the average
+        assert!((ratings[0].0 + ratings[1].0 - 2. * AVERAGE_RATING).abs() < 1e-6);
+        assert_eq!(standings.get_win_rate(0), 0.9);
+
+        // a bot winning every match keeps a finite rating
+        let standings = Standings::new(2, &get_results(&[(1, 0); 10]));
+        assert!(standings.get_ratings()[0].0.is_finite());
+    }
+
+    #[test]
+    fn a_draw_is_half_a_win() {
+        let draws = Standings::new(2, &get_results(&[(1, 1); 4]));
+        let split = Standings::new(2, &get_results(&[(2, 1), (1, 2), (2, 1), (1, 2)]));
+        assert_eq!(draws.wins, split.wins);
+        assert_eq!(draws.get_ratings(), split.get_ratings());
+    }
+
+    #[test]
+    fn the_interval_narrows_with_more_matches_and_failed_matches_do_not_count() {
+        let few = Standings::new(2, &get_results(&[(1, 0), (0, 1)]));
+        let many = Standings::new(2, &get_results(&[(1, 0), (0, 1)].repeat(20)));
+        assert!(many.get_ratings()[0].1 < few.get_ratings()[0].1);
+
+        let mut results = get_results(&[(1, 0)]);
+        results[0].scores = None;
+        let standings = Standings::new(2, &results);
+        assert_eq!(standings.games[0][1], 0.);
+        assert_eq!(standings.get_ratings()[0].1, f64::INFINITY);
+    }
+}
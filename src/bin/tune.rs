@@ -0,0 +1,532 @@
+// tunes the parameters of a bot (see `src/params.rs`) on games played with its local referee, then gives the best
+// values as the constants of the bot
+//
+// usage: tune [--game code-a-la-mode|back-to-the-code|code-vs-zombies|the-labyrinth] [--iterations N]
+//             [--candidates N] [--seeds N] [--threads N] [--referee-args ARGS] [--write]
+//
+// the scenarios are the seeds 1 to `--seeds`, a parameter set is rated by the mean score of the bot over them:
+// - code-a-la-mode: with the idle then the server partner, without then with tarts
+// - back-to-the-code: against `scripted:random`
+// - code-vs-zombies: the map the referee places at random from the seed
+// - the-labyrinth: a maze generated from the seed (see `src/maze.rs`, 11 to 21 rows, 21 to 51 columns) played by
+//   the `cases` runner, the fewer turns the better (no referee arguments), the hand-written cases of `tests/cases`
+//   are left out
+// a set failing a scenario (a crash, a maze lost) is rejected rather than scored, the bot and its referee are looked
+// for next to this binary, the defaults are the constants of `src/bin/<bot>.rs` and `--write` replaces them with the
+// best values
+//
+// the search is a (1+λ) evolution strategy: `--candidates` sets are drawn around the best one so far with a gaussian
+// step, the step grows after an improvement and shrinks otherwise
+//
+// tune --game back-to-the-code --seeds 6 --referee-args "--rounds 150"
+
+use codinggame_backtocode_rust::maze;
+use codinggame_backtocode_rust::random::Random;
+use codinggame_backtocode_rust::referee::{self, GameKind};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// the first step, a share of the range of each parameter
+const INITIAL_STEP: f64 = 0.3;
+const MIN_STEP: f64 = 0.02;
+const STEP_GROWTH: f64 = 1.5;
+const STEP_SHRINK: f64 = 0.8;
+
+/// a constant of the bot the tuner can change, within [min, max]
+struct Param {
+    name: &'static str,
+    min: i64,
+    max: i64,
+}
+
+/// what the tuner plays
+#[derive(Copy, Clone, PartialEq)]
+enum Target {
+    /// against the local referee of the game, the score of the bot
+    Game(GameKind),
+    /// generated mazes played by the cases runner, the turns Kirk took to get back
+    TheLabyrinth,
+}
+
+impl Target {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "the-labyrinth" => Some(Target::TheLabyrinth),
+            _ => GameKind::parse(name).map(Target::Game),
+        }
+    }
+
+    pub fn get_bot(&self) -> &'static str {
+        match self {
+            Target::Game(game) => game.get_bot(),
+            Target::TheLabyrinth => "the-labyrinth",
+        }
+    }
+
+    /// the referee, or the runner of the cases
+    pub fn get_referee(&self) -> &'static str {
+        match self {
+            Target::Game(game) => game.get_referee(),
+            Target::TheLabyrinth => "cases",
+        }
+    }
+
+    pub fn get_params(&self) -> Vec<Param> {
+        match self {
+            Target::Game(GameKind::BackToTheCode) => vec![
+                Param {
+                    name: "BACK_IN_TIME_MIN_GAIN",
+                    min: 0,
+                    max: 30,
+                },
+                Param {
+                    name: "MAX_SAFETY_CHECKS",
+                    min: 1,
+                    max: 200,
+                },
+                Param {
+                    name: "DISTANCE_WEIGHT",
+                    min: 0,
+                    max: 300,
+                },
+                Param {
+                    name: "AREA_EXPONENT",
+                    min: 50,
+                    max: 200,
+                },
+            ],
+            Target::Game(GameKind::CodeALaMode) => vec![
+                Param {
+                    name: "OVEN_MARGIN",
+                    min: 0,
+                    max: 6,
+                },
+                Param {
+                    name: "LOOKAHEAD_CUSTOMERS",
+                    min: 0,
+                    max: 8,
+                },
+            ],
+            Target::Game(GameKind::CodeVsZombies) => vec![Param {
+                name: "SHOOT_SLACK",
+                min: -2000,
+                max: 1500,
+            }],
+            Target::TheLabyrinth => vec![Param {
+                name: "COMMAND_ROOM_COST",
+                min: 1,
+                max: 40,
+            }],
+        }
+    }
+
+    /// the referee arguments of every scenario, for the labyrinth the directory of the cases of its maze
+    pub fn get_scenarios(&self, seeds: u64) -> Vec<Vec<String>> {
+        let Target::Game(game) = self else {
+            return write_labyrinth_cases(seeds);
+        };
+        let mut scenarios = Vec::new();
+        for seed in 1..=seeds {
+            let seed = vec!["--seed".to_string(), seed.to_string()];
+            match game {
+                GameKind::BackToTheCode | GameKind::CodeVsZombies => scenarios.push(seed),
+                GameKind::CodeALaMode => {
+                    for partner in ["scripted:idle", "scripted:server"] {
+                        for tarts in [false, true] {
+                            let mut args = seed.clone();
+                            if tarts {
+                                args.push("--tarts".to_string());
+                            }
+                            args.push(partner.to_string());
+                            scenarios.push(args);
+                        }
+                    }
+                }
+            }
+        }
+        scenarios
+    }
+}
+
+/// where the mazes of the labyrinth are written while tuning
+fn get_labyrinth_dir() -> PathBuf {
+    env::temp_dir().join(format!("tune-the-labyrinth-{}", process::id()))
+}
+
+/// writes the maze of every seed as a case of the cases runner, one cases directory per maze
+fn write_labyrinth_cases(seeds: u64) -> Vec<Vec<String>> {
+    (1..=seeds)
+        .map(|seed| {
+            let mut random = Random::new(seed);
+            let rows = 2 * random.range(5, 11) + 1;
+            let columns = 2 * random.range(10, 26) + 1;
+            let (rows_of_maze, alarm) = maze::generate(&mut random, rows, columns);
+            let dir = get_labyrinth_dir().join(seed.to_string());
+            let puzzle_dir = dir.join("the-labyrinth");
+            fs::create_dir_all(&puzzle_dir)
+                .unwrap_or_else(|e| panic!("can not create {}: {}", puzzle_dir.display(), e));
+            let mut case = format!(
+                "the maze of seed {}\n[input]\n{} {} {}\n[referee]\n",
+                seed, rows, columns, alarm
+            );
+            for row in rows_of_maze {
+                case.push_str(&row.into_iter().collect::<String>());
+                case.push('\n');
+            }
+            let path = puzzle_dir.join("maze.txt");
+            fs::write(&path, case)
+                .unwrap_or_else(|e| panic!("can not write {}: {}", path.display(), e));
+            vec![dir.to_string_lossy().to_string()]
+        })
+        .collect()
+}
+
+struct Tuner {
+    target: Target,
+    params: Vec<Param>,
+    bot: PathBuf,
+    referee: PathBuf,
+    referee_args: Vec<String>,
+    scenarios: Vec<Vec<String>>,
+    threads: usize,
+    /// the mean score of every parameter set tried
+    scores: HashMap<Vec<i64>, f64>,
+}
+
+impl Tuner {
+    /// the score of the bot in one scenario, `None` if it failed
+    fn play(&self, values: &[i64], scenario: &[String]) -> Option<f64> {
+        let params = self
+            .params
+            .iter()
+            .zip(values)
+            .map(|(param, value)| format!("{}={}", param.name.to_lowercase(), value))
+            .collect::<Vec<_>>();
+        let game = match self.target {
+            Target::Game(game) => game,
+            Target::TheLabyrinth => return self.play_cases(&scenario[0], "the-labyrinth", &params),
+        };
+        let mut bot = self.bot.to_string_lossy().to_string();
+        for param in &params {
+            bot.push_str(&format!(" --param {}", param));
+        }
+        // the bot comes first, the partner or the opponent after it
+        let (options, others) = match game {
+            GameKind::BackToTheCode => (scenario, vec!["scripted:random".to_string()]),
+            GameKind::CodeVsZombies => (scenario, Vec::new()),
+            GameKind::CodeALaMode => (
+                &scenario[..scenario.len() - 1],
+                vec![scenario[scenario.len() - 1].clone()],
+            ),
+        };
+        let mut args = options.to_vec();
+        args.extend(self.referee_args.iter().cloned());
+        args.push(bot);
+        args.extend(others);
+        let scores = referee::run_referee(game, &self.referee, &args)?;
+        scores.first().map(|score| *score as f64)
+    }
+
+    /// minus the turns the bot took over the cases of the puzzle, `None` if it failed one
+    fn play_cases(&self, cases_dir: &str, puzzle: &str, params: &[String]) -> Option<f64> {
+        // the runner starts the bot, the parameters go through the environment
+        let output = Command::new(&self.referee)
+            .arg("--cases")
+            .arg(cases_dir)
+            .arg(puzzle)
+            .env("PARAMS", params.join(","))
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        // ok   the-labyrinth/maze: back at the start in 24 turns
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let turns = stdout
+            .lines()
+            .filter_map(|line| {
+                line.strip_suffix(" turns")?
+                    .rsplit(' ')
+                    .next()?
+                    .parse::<f64>()
+                    .ok()
+            })
+            .sum::<f64>();
+        Some(-turns)
+    }
+
+    /// plays every scenario of the sets not tried yet, on `threads` threads
+    pub fn evaluate(&mut self, candidates: &[Vec<i64>]) {
+        let mut jobs = Vec::new();
+        for values in candidates {
+            if self.scores.contains_key(values) || jobs.iter().any(|(known, _)| known == values) {
+                continue;
+            }
+            for scenario in 0..self.scenarios.len() {
+                jobs.push((values.clone(), scenario));
+            }
+        }
+        let next = AtomicUsize::new(0);
+        // `None` once a scenario failed
+        let totals = Mutex::new(HashMap::<Vec<i64>, Option<(f64, usize)>>::new());
+        thread::scope(|scope| {
+            for _ in 0..self.threads.max(1) {
+                scope.spawn(|| loop {
+                    let Some((values, scenario)) = jobs.get(next.fetch_add(1, Ordering::Relaxed))
+                    else {
+                        return;
+                    };
+                    let score = self.play(values, &self.scenarios[*scenario]);
+                    let mut totals = totals.lock().unwrap();
+                    let total = totals.entry(values.clone()).or_insert(Some((0., 0)));
+                    match (score, total.as_mut()) {
+                        (Some(score), Some((sum, count))) => {
+                            *sum += score;
+                            *count += 1;
+                        }
+                        (Some(_), None) => {}
+                        (None, _) => {
+                            eprintln!(
+                                "{:?} {:?}: the referee failed, the set is rejected",
+                                values, self.scenarios[*scenario]
+                            );
+                            *total = None;
+                        }
+                    }
+                });
+            }
+        });
+        // a failed run would weigh like a real score of 0, the whole set is rejected instead
+        for (values, total) in totals.into_inner().unwrap() {
+            let score = total.map_or(f64::MIN, |(sum, count)| sum / count as f64);
+            self.scores.insert(values, score);
+        }
+    }
+
+    /// the mean score of the set, `f64::MIN` if it was rejected or not tried
+    pub fn get_score(&self, values: &[i64]) -> f64 {
+        self.scores.get(values).copied().unwrap_or(f64::MIN)
+    }
+}
+
+fn format_score(score: f64) -> String {
+    if score == f64::MIN {
+        "rejected".to_string()
+    } else {
+        format!("{:.1}", score)
+    }
+}
+
+/// the value of `const NAME: T = value;` in the source
+fn read_constant(source: &str, name: &str) -> Option<i64> {
+    let prefix = format!("const {}:", name);
+    let line = source.lines().find(|line| line.starts_with(&prefix))?;
+    let (_, value) = line.split_once('=')?;
+    value.trim().trim_end_matches(';').trim().parse().ok()
+}
+
+/// the source with the value of `const NAME: T = value;` replaced
+fn write_constant(source: &str, name: &str, value: i64) -> String {
+    let prefix = format!("const {}:", name);
+    source
+        .lines()
+        .map(|line| match line.split_once('=') {
+            Some((declaration, _)) if line.starts_with(&prefix) => {
+                format!("{}= {};", declaration, value)
+            }
+            _ => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        + if source.ends_with('\n') { "\n" } else { "" }
+}
+
+/// a normal random number (Box-Muller)
+fn get_gaussian(random: &mut Random) -> f64 {
+    let u = random.next_f64().max(f64::MIN_POSITIVE);
+    let v = random.next_f64();
+    (-2. * u.ln()).sqrt() * (2. * std::f64::consts::PI * v).cos()
+}
+
+fn main() {
+    let mut target = Target::Game(GameKind::CodeALaMode);
+    let mut iterations = 20;
+    let mut candidate_count = 4;
+    let mut seeds = 5;
+    let mut threads = thread::available_parallelism().map_or(1, |count| count.get());
+    let mut referee_args = Vec::new();
+    let mut write = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .unwrap_or_else(|| panic!("{} needs a value", name))
+        };
+        match arg.as_str() {
+            "--game" => {
+                let name = value("--game");
+                target = Target::parse(&name).unwrap_or_else(|| panic!("unknown game {}", name));
+            }
+            "--iterations" => iterations = value("--iterations").parse().unwrap(),
+            "--candidates" => candidate_count = value("--candidates").parse().unwrap(),
+            "--seeds" => seeds = value("--seeds").parse().unwrap(),
+            "--threads" => threads = value("--threads").parse().unwrap(),
+            "--referee-args" => {
+                referee_args = value("--referee-args")
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect()
+            }
+            "--write" => write = true,
+            _ => {
+                eprintln!("usage: tune [--game code-a-la-mode|back-to-the-code|code-vs-zombies|the-labyrinth] [--iterations N] [--candidates N] [--seeds N] [--threads N] [--referee-args ARGS] [--write]");
+                process::exit(2);
+            }
+        }
+    }
+
+    let source_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("src/bin")
+        .join(format!("{}.rs", target.get_bot()));
+    let source = fs::read_to_string(&source_path)
+        .unwrap_or_else(|e| panic!("can not read {}: {}", source_path.display(), e));
+    let params = target.get_params();
+    let defaults = params
+        .iter()
+        .map(|param| {
+            read_constant(&source, param.name).unwrap_or_else(|| {
+                panic!("no constant {} in {}", param.name, source_path.display())
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut tuner = Tuner {
+        target,
+        params,
+        bot: referee::find_bin(target.get_bot()),
+        referee: referee::find_bin(target.get_referee()),
+        referee_args,
+        scenarios: target.get_scenarios(seeds),
+        threads,
+        scores: HashMap::new(),
+    };
+
+    tuner.evaluate(std::slice::from_ref(&defaults));
+    let default_score = tuner.get_score(&defaults);
+    eprintln!("defaults {:?}: {}", defaults, format_score(default_score));
+
+    let mut random = Random::new(1);
+    let mut best = defaults.clone();
+    let mut step = INITIAL_STEP;
+    for iteration in 1..=iterations {
+        let candidates = (0..candidate_count)
+            .map(|_| {
+                tuner
+                    .params
+                    .iter()
+                    .zip(&best)
+                    .map(|(param, value)| {
+                        let range = (param.max - param.min) as f64;
+                        let moved = *value as f64 + get_gaussian(&mut random) * step * range;
+                        (moved.round() as i64).clamp(param.min, param.max)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        tuner.evaluate(&candidates);
+
+        let best_candidate = candidates
+            .iter()
+            .max_by(|a, b| tuner.get_score(a).total_cmp(&tuner.get_score(b)))
+            .cloned();
+        match best_candidate {
+            Some(candidate) if tuner.get_score(&candidate) > tuner.get_score(&best) => {
+                best = candidate;
+                step *= STEP_GROWTH;
+            }
+            _ => step = (step * STEP_SHRINK).max(MIN_STEP),
+        }
+        eprintln!(
+            "iteration {}: best {:?} {}, step {:.3}",
+            iteration,
+            best,
+            format_score(tuner.get_score(&best)),
+            step
+        );
+    }
+
+    if target == Target::TheLabyrinth {
+        let _ = fs::remove_dir_all(get_labyrinth_dir());
+    }
+
+    println!(
+        "// {} scenarios, {} sets tried: {} -> {}",
+        tuner.scenarios.len(),
+        tuner.scores.len(),
+        format_score(default_score),
+        format_score(tuner.get_score(&best))
+    );
+    let mut tuned_source = source.clone();
+    for (param, value) in tuner.params.iter().zip(&best) {
+        let line = source
+            .lines()
+            .find(|line| line.starts_with(&format!("const {}:", param.name)))
+            .unwrap_or_default();
+        let declaration = line
+            .split_once('=')
+            .map_or(line, |(declaration, _)| declaration);
+        println!("{}= {};", declaration, value);
+        tuned_source = write_constant(&tuned_source, param.name, *value);
+    }
+    if write && best != defaults {
+        fs::write(&source_path, tuned_source)
+            .unwrap_or_else(|e| panic!("can not write {}: {}", source_path.display(), e));
+        eprintln!("{} updated", source_path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "\
+/// margin before the item burns
+const OVEN_MARGIN: i32 = 2;
+const OVEN_MARGIN_SLOW: i32 = 5;
+const LOOKAHEAD_CUSTOMERS: usize = 3;
+    let oven_margin = OVEN_MARGIN;
+";
+
+    #[test]
+    fn constants_are_read_by_name() {
+        assert_eq!(read_constant(SOURCE, "OVEN_MARGIN"), Some(2));
+        assert_eq!(read_constant(SOURCE, "OVEN_MARGIN_SLOW"), Some(5));
+        assert_eq!(read_constant(SOURCE, "LOOKAHEAD_CUSTOMERS"), Some(3));
+        assert_eq!(read_constant(SOURCE, "MISSING"), None);
+        assert_eq!(read_constant("const NAME: &str = \"a\";", "NAME"), None);
+    }
+
+    #[test]
+    fn a_written_constant_is_read_back() {
+        for value in [0, 7, -3, 1000] {
+            let source = write_constant(SOURCE, "OVEN_MARGIN", value);
+            assert_eq!(read_constant(&source, "OVEN_MARGIN"), Some(value));
+            // the other lines do not change, nor the end of the file
+            assert_eq!(read_constant(&source, "OVEN_MARGIN_SLOW"), Some(5));
+            assert_eq!(source.lines().count(), SOURCE.lines().count());
+            assert!(source.ends_with("= OVEN_MARGIN;\n"));
+            assert!(source.contains(&format!("const OVEN_MARGIN: i32 = {};\n", value)));
+        }
+        let source = write_constant(SOURCE, "LOOKAHEAD_CUSTOMERS", 3);
+        assert_eq!(source, SOURCE);
+        let source = write_constant(SOURCE.trim_end(), "LOOKAHEAD_CUSTOMERS", 4);
+        assert!(source.ends_with("OVEN_MARGIN;"));
+    }
+}
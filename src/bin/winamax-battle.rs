@@ -1,6 +1,7 @@
 // https://www.codingame.com/ide/puzzle/winamax-battle
 
-use std::{collections::VecDeque, fmt, io};
+use codinggame_backtocode_rust::{debug, trace, transcript, write_answer};
+use std::{collections::VecDeque, fmt};
 
 macro_rules! parse_input {
     ($x:expr, $t:ident) => {
@@ -52,19 +53,19 @@ impl Card {
             card_str = s.get(0..(s.len() - 1)).unwrap();
         }
         match card_str {
-            "2" => return Card::Two,
-            "3" => return Card::Three,
-            "4" => return Card::Foor,
-            "5" => return Card::Five,
-            "6" => return Card::Six,
-            "7" => return Card::Seven,
-            "8" => return Card::Height,
-            "9" => return Card::Nine,
-            "10" => return Card::Ten,
-            "J" => return Card::J,
-            "Q" => return Card::Q,
-            "K" => return Card::K,
-            "A" => return Card::A,
+            "2" => Card::Two,
+            "3" => Card::Three,
+            "4" => Card::Foor,
+            "5" => Card::Five,
+            "6" => Card::Six,
+            "7" => Card::Seven,
+            "8" => Card::Height,
+            "9" => Card::Nine,
+            "10" => Card::Ten,
+            "J" => Card::J,
+            "Q" => Card::Q,
+            "K" => Card::K,
+            "A" => Card::A,
             _ => {
                 panic!("unknown card: {} ({})", card_str, s)
             }
@@ -78,22 +79,22 @@ impl Card {
  **/
 fn main() {
     let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
+    transcript::read_line(&mut input_line);
     let n = parse_input!(input_line, i32); // the number of cards for player 1
     let mut p1_cards = VecDeque::<Card>::new();
     let mut p2_cards = VecDeque::<Card>::new();
     for _ in 0..n as usize {
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         let cardp_1 = input_line.trim().to_string(); // the n cards of player 1
         p1_cards.push_back(Card::from_str(&cardp_1));
     }
     let mut input_line = String::new();
-    io::stdin().read_line(&mut input_line).unwrap();
+    transcript::read_line(&mut input_line);
     let m = parse_input!(input_line, i32); // the number of cards for player 2
     for _ in 0..m as usize {
         let mut input_line = String::new();
-        io::stdin().read_line(&mut input_line).unwrap();
+        transcript::read_line(&mut input_line);
         let cardp_2 = input_line.trim().to_string(); // the m cards of player 2
         p2_cards.push_back(Card::from_str(&cardp_2));
     }
@@ -101,8 +102,8 @@ fn main() {
     // Write an answer using println!("message...");
     // To debug: eprintln!("Debug message...");
 
-    eprintln!("p1_cards: {:?}", p1_cards);
-    eprintln!("p2_cards: {:?}", p2_cards);
+    debug!("p1_cards: {:?}", p1_cards);
+    debug!("p2_cards: {:?}", p2_cards);
 
     //
     let mut round = 0;
@@ -113,17 +114,17 @@ fn main() {
 
         let Some(p1_card) = p1_cards.pop_front() else {
             if p1_stack.len() + p2_stack.len() > 0 {
-                println!("PAT")
+                write_answer!("PAT")
             } else {
-                println!("2 {}", round);
+                write_answer!("2 {}", round);
             }
             return;
         };
         let Some(p2_card) = p2_cards.pop_front() else {
             if p1_stack.len() + p2_stack.len() > 0 {
-                println!("PAT")
+                write_answer!("PAT")
             } else {
-                println!("1 {}", round);
+                write_answer!("1 {}", round);
             }
             return;
         };
@@ -137,33 +138,33 @@ fn main() {
         if p1_card == p2_card {
             for _ in 0..3 {
                 let Some(card) = p1_cards.pop_front() else {
-                    println!("PAT");
+                    write_answer!("PAT");
                     return;
                 };
                 p1_stack.push_back(card);
             }
             for _ in 0..3 {
                 let Some(card) = p2_cards.pop_front() else {
-                    println!("PAT");
+                    write_answer!("PAT");
                     return;
                 };
                 p2_stack.push_back(card);
             }
-            eprintln!("stack: p1={:?} | p2={:?}", p1_stack, p2_stack);
+            trace!("stack: p1={:?} | p2={:?}", p1_stack, p2_stack);
         } else {
             round += 1;
             if p2_card > p1_card {
-                while p1_stack.len() > 0 {
+                while !p1_stack.is_empty() {
                     p2_cards.push_back(p1_stack.pop_front().unwrap());
                 }
-                while p2_stack.len() > 0 {
+                while !p2_stack.is_empty() {
                     p2_cards.push_back(p2_stack.pop_front().unwrap());
                 }
             } else if p1_card > p2_card {
-                while p1_stack.len() > 0 {
+                while !p1_stack.is_empty() {
                     p1_cards.push_back(p1_stack.pop_front().unwrap());
                 }
-                while p2_stack.len() > 0 {
+                while !p2_stack.is_empty() {
                     p1_cards.push_back(p2_stack.pop_front().unwrap());
                 }
             }
@@ -0,0 +1,12 @@
+// code shared by the bots and the tools around them (referees, replay), `src/bin/bundle.rs` pastes it after a bot
+// for CodinGame
+
+pub mod log;
+pub mod maze;
+pub mod params;
+pub mod process;
+pub mod random;
+pub mod referee;
+pub mod territory;
+pub mod timer;
+pub mod transcript;
@@ -0,0 +1,159 @@
+// debug lines on stderr with levels and categories
+//
+// `error!`, `warn!`, `info!`, `debug!` and `trace!` print like `eprintln!`, the category of a line is the module it
+// comes from (`code_a_la_mode` for a bot) or the one given with `debug!(target: "grid", ...)`
+//
+// the `LOG` environment variable picks the lines printed: a level for every category then levels per category,
+// `LOG=info,grid=off,astar=trace`, everything is printed when it is not set
+//
+// the `submission` feature strips the lines under `info` at compile time (CodinGame keeps a limited stderr and
+// formatting big dumps slows the turns), `src/bin/bundle.rs` turns it on in the source pasted into CodinGame
+
+use std::env;
+use std::sync::OnceLock;
+
+#[derive(Copy, Clone, Eq, Ord, PartialEq, PartialOrd, Debug)]
+pub enum Level {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "off" => Some(Level::Off),
+            "error" => Some(Level::Error),
+            "warn" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+}
+
+/// lines above this level are not compiled in
+#[cfg(feature = "submission")]
+pub const MAX_LEVEL: Level = Level::Info;
+#[cfg(not(feature = "submission"))]
+pub const MAX_LEVEL: Level = Level::Trace;
+
+/// the levels asked with `LOG`
+struct Filter {
+    level: Level,
+    categories: Vec<(String, Level)>,
+}
+
+impl Filter {
+    /// `level,category=level,...`, the parts we can not read are ignored
+    fn parse(spec: &str) -> Self {
+        let mut filter = Self {
+            level: Level::Trace,
+            categories: Vec::new(),
+        };
+        for part in spec.split(',').filter(|part| !part.trim().is_empty()) {
+            match part.split_once('=') {
+                Some((category, level)) => match Level::parse(level) {
+                    Some(level) => filter.categories.push((category.trim().to_string(), level)),
+                    None => eprintln!("LOG: unknown level in {}", part),
+                },
+                None => match Level::parse(part) {
+                    Some(level) => filter.level = level,
+                    None => eprintln!("LOG: unknown level {}", part),
+                },
+            }
+        }
+        filter
+    }
+
+    /// the level of the category, `grid` also sets the level of `grid::distances`
+    fn get_level(&self, category: &str) -> Level {
+        self.categories
+            .iter()
+            .rev()
+            .find(|(name, _)| {
+                category
+                    .strip_prefix(name.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map_or(self.level, |(_, level)| *level)
+    }
+}
+
+fn get_filter() -> &'static Filter {
+    static FILTER: OnceLock<Filter> = OnceLock::new();
+    FILTER.get_or_init(|| Filter::parse(&env::var("LOG").unwrap_or_default()))
+}
+
+/// a line of this level and category is printed
+pub fn is_enabled(level: Level, category: &str) -> bool {
+    level <= MAX_LEVEL && level <= get_filter().get_level(category)
+}
+
+/// prints the line like `eprintln!` when its level and category are enabled, the arguments are not evaluated
+/// otherwise
+#[macro_export]
+macro_rules! log {
+    (target: $target:expr, $level:expr, $($arg:tt)+) => {
+        if $level <= $crate::log::MAX_LEVEL && $crate::log::is_enabled($level, $target) {
+            eprintln!($($arg)+);
+        }
+    };
+    ($level:expr, $($arg:tt)+) => {
+        $crate::log!(target: module_path!(), $level, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::log::Level::Error, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Error, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! warn {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::log::Level::Warn, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Warn, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! info {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::log::Level::Info, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Info, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! debug {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::log::Level::Debug, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Debug, $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! trace {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::log::Level::Trace, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Trace, $($arg)+)
+    };
+}
@@ -0,0 +1,80 @@
+// generated mazes of The Labyrinth, for the bot tests and the tuner (see `src/bin/tune.rs`)
+//
+// a maze is its rows: `#` a wall, `.` a passage, `T` where Kirk starts and `C` the command room
+
+use crate::random::Random;
+use std::collections::VecDeque;
+
+/// a `rows`x`columns` maze dug from (1, 1) by a random depth-first walk, with a share of its inner walls opened
+/// to make loops, `T` and `C` on passages far from each other, and an alarm a bit longer than the way back
+pub fn generate(random: &mut Random, rows: i32, columns: i32) -> (Vec<Vec<char>>, i32) {
+    let mut maze = vec![vec!['#'; columns as usize]; rows as usize];
+    let is_inside = |x: i32, y: i32| x > 0 && y > 0 && x < columns - 1 && y < rows - 1;
+    let mut stack = vec![(1, 1)];
+    maze[1][1] = '.';
+    while let Some(&(x, y)) = stack.last() {
+        let next = [(2, 0), (-2, 0), (0, 2), (0, -2)]
+            .into_iter()
+            .filter(|(dx, dy)| {
+                is_inside(x + dx, y + dy) && maze[(y + dy) as usize][(x + dx) as usize] == '#'
+            })
+            .collect::<Vec<_>>();
+        let Some(&(dx, dy)) = random.choose(&next) else {
+            stack.pop();
+            continue;
+        };
+        maze[(y + dy / 2) as usize][(x + dx / 2) as usize] = '.';
+        maze[(y + dy) as usize][(x + dx) as usize] = '.';
+        stack.push((x + dx, y + dy));
+    }
+    for y in 1..rows - 1 {
+        for x in 1..columns - 1 {
+            let between = (maze[y as usize][(x - 1) as usize] == '.'
+                && maze[y as usize][(x + 1) as usize] == '.')
+                || (maze[(y - 1) as usize][x as usize] == '.'
+                    && maze[(y + 1) as usize][x as usize] == '.');
+            if maze[y as usize][x as usize] == '#' && between && random.chance(0.1) {
+                maze[y as usize][x as usize] = '.';
+            }
+        }
+    }
+
+    let passages = (0..rows)
+        .flat_map(|y| (0..columns).map(move |x| (x, y)))
+        .filter(|(x, y)| maze[*y as usize][*x as usize] == '.')
+        .collect::<Vec<_>>();
+    let start = *random.choose(&passages).unwrap();
+    let distances = get_distances(&maze, start);
+    let farthest = passages
+        .iter()
+        .map(|(x, y)| distances[*y as usize][*x as usize])
+        .max();
+    let far = passages
+        .iter()
+        .filter(|(x, y)| 2 * distances[*y as usize][*x as usize] >= farthest.unwrap())
+        .copied()
+        .collect::<Vec<_>>();
+    let command = *random.choose(&far).unwrap();
+    maze[start.1 as usize][start.0 as usize] = 'T';
+    maze[command.1 as usize][command.0 as usize] = 'C';
+    let alarm = distances[command.1 as usize][command.0 as usize] + 5;
+    (maze, alarm)
+}
+
+/// the steps from the position to every cell of the maze
+pub fn get_distances(maze: &[Vec<char>], from: (i32, i32)) -> Vec<Vec<i32>> {
+    let mut distances = vec![vec![i32::MAX; maze[0].len()]; maze.len()];
+    distances[from.1 as usize][from.0 as usize] = 0;
+    let mut queue = VecDeque::from([from]);
+    while let Some((x, y)) = queue.pop_front() {
+        for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+            if maze[ny as usize][nx as usize] != '#'
+                && distances[ny as usize][nx as usize] == i32::MAX
+            {
+                distances[ny as usize][nx as usize] = distances[y as usize][x as usize] + 1;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    distances
+}
@@ -0,0 +1,52 @@
+// heuristic values of a bot that can change without a rebuild, to tune them (see `src/bin/tune.rs`)
+//
+// a parameter is named after the constant holding its default, lowercased (`OVEN_MARGIN` is `oven_margin`), the
+// `PARAMS` environment variable (`PARAMS=oven_margin=3,lookahead_customers=2`) or the `--param name=value`
+// arguments override it, the arguments win
+
+use std::env;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// the `name=value` overrides, the last one of a name wins
+fn get_overrides() -> &'static Vec<(String, String)> {
+    static OVERRIDES: OnceLock<Vec<(String, String)>> = OnceLock::new();
+    OVERRIDES.get_or_init(|| {
+        let mut overrides = Vec::new();
+        let mut add = |pair: &str| match pair.split_once('=') {
+            Some((name, value)) => {
+                overrides.push((name.trim().to_string(), value.trim().to_string()))
+            }
+            None => eprintln!("params: `{}` is not name=value", pair),
+        };
+        if let Ok(pairs) = env::var("PARAMS") {
+            pairs
+                .split(',')
+                .filter(|pair| !pair.is_empty())
+                .for_each(&mut add);
+        }
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            if arg == "--param" {
+                if let Some(pair) = args.next() {
+                    add(&pair);
+                }
+            }
+        }
+        overrides
+    })
+}
+
+/// the value of the parameter, its default if not overridden (or overridden by something we can not read)
+pub fn get<T: FromStr>(name: &str, default: T) -> T {
+    let Some((_, value)) = get_overrides().iter().rev().find(|(key, _)| key == name) else {
+        return default;
+    };
+    value.parse().unwrap_or_else(|_| {
+        eprintln!(
+            "params: can not read {}={}, keeping the default",
+            name, value
+        );
+        default
+    })
+}
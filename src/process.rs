@@ -0,0 +1,62 @@
+// a bot running as a child process, talking over its stdin/stdout
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+pub struct BotProcess {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl BotProcess {
+    /// starts the command (split on whitespace), its stderr is shown only when verbose
+    pub fn spawn(command: &str, verbose: bool) -> Self {
+        let mut parts = command.split_whitespace();
+        let program = parts.next().expect("empty bot command");
+        let mut child = Command::new(program)
+            .args(parts)
+            // the bot records its own transcript only when asked by its command line
+            .env_remove("TRANSCRIPT")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(if verbose {
+                Stdio::inherit()
+            } else {
+                Stdio::null()
+            })
+            .spawn()
+            .unwrap_or_else(|e| panic!("can not start bot {}: {}", command, e));
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Self {
+            child,
+            stdin,
+            stdout,
+        }
+    }
+
+    /// sends lines to the bot, a dead bot is detected when reading its answer
+    pub fn send(&mut self, lines: &[String]) {
+        for line in lines {
+            let _ = writeln!(self.stdin, "{}", line);
+        }
+        let _ = self.stdin.flush();
+    }
+
+    /// reads the answer of the turn, `None` if the bot died
+    pub fn receive(&mut self) -> Option<String> {
+        let mut line = String::new();
+        match self.stdout.read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line.trim().to_string()),
+        }
+    }
+}
+
+impl Drop for BotProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,80 @@
+// seeded random numbers without a crate, the same seed always gives the same numbers (referees, generators, bots
+// replaying a game)
+//
+// a xorshift64, fast and good enough for games, not for anything secret
+
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            // spreads the small seeds over the bits, the state is never 0
+            state: seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1,
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// a number in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// a number in [min, max)
+    pub fn range(&mut self, min: i32, max: i32) -> i32 {
+        assert!(min < max, "empty range {}..{}", min, max);
+        min + (self.next_u64() % (max as i64 - min as i64) as u64) as i32
+    }
+
+    /// a number in [min, max)
+    pub fn range_f64(&mut self, min: f64, max: f64) -> f64 {
+        min + self.next_f64() * (max - min)
+    }
+
+    /// true with the given probability
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        items.get(self.range(0, items.len() as i32) as usize)
+    }
+
+    /// the index of an item picked in proportion to its weight, `None` if no weight is positive
+    pub fn choose_weighted(&mut self, weights: &[f64]) -> Option<usize> {
+        let total = weights.iter().filter(|weight| **weight > 0.).sum::<f64>();
+        if total <= 0. {
+            return None;
+        }
+        let mut target = self.next_f64() * total;
+        for (index, weight) in weights.iter().enumerate() {
+            if *weight <= 0. {
+                continue;
+            }
+            if target < *weight {
+                return Some(index);
+            }
+            target -= weight;
+        }
+        // rounding errors, the last positive weight
+        weights.iter().rposition(|weight| *weight > 0.)
+    }
+
+    /// Fisher-Yates
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            let other = self.range(0, index as i32 + 1) as usize;
+            items.swap(index, other);
+        }
+    }
+}
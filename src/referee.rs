@@ -0,0 +1,72 @@
+// the games played with a local referee, run by the tools comparing or tuning bots (see `src/bin/tournament.rs` and
+// `src/bin/tune.rs`)
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum GameKind {
+    BackToTheCode,
+    CodeALaMode,
+    CodeVsZombies,
+}
+
+impl GameKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "back-to-the-code" => Some(GameKind::BackToTheCode),
+            "code-a-la-mode" => Some(GameKind::CodeALaMode),
+            "code-vs-zombies" => Some(GameKind::CodeVsZombies),
+            _ => None,
+        }
+    }
+
+    pub fn get_bot(&self) -> &'static str {
+        match self {
+            GameKind::BackToTheCode => "back-to-the-code",
+            GameKind::CodeALaMode => "code-a-la-mode",
+            GameKind::CodeVsZombies => "code-vs-zombies",
+        }
+    }
+
+    pub fn get_referee(&self) -> &'static str {
+        match self {
+            GameKind::BackToTheCode => "back-to-the-code-referee",
+            GameKind::CodeALaMode => "code-a-la-mode-referee",
+            GameKind::CodeVsZombies => "code-vs-zombies-referee",
+        }
+    }
+
+    /// the lines of the referee output ending with the score of a player, in the order of the players
+    pub fn is_score_line(&self, line: &str) -> bool {
+        match self {
+            GameKind::BackToTheCode => line.starts_with("player "),
+            GameKind::CodeALaMode => line.starts_with("total:"),
+            GameKind::CodeVsZombies => line.starts_with("score:"),
+        }
+    }
+}
+
+/// a binary built next to the running one (`target/debug` under `cargo test`)
+pub fn find_bin(name: &str) -> PathBuf {
+    env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .unwrap_or_default()
+        .join(name)
+}
+
+/// runs the referee of the game and reads the score of every player, `None` when it failed
+pub fn run_referee(game: GameKind, referee: &Path, args: &[String]) -> Option<Vec<i64>> {
+    let output = Command::new(referee).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .filter(|line| game.is_score_line(line))
+        .map(|line| line.rsplit(' ').next()?.parse().ok())
+        .collect()
+}
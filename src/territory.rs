@@ -0,0 +1,94 @@
+// the territory rules of Back to the Code, shared by the bot simulating rounds and the local referee so both always
+// agree on the owner of every cell
+//
+// the board is a list of owners indexed by `y * width + x`, a player id or -1 for a neutral cell
+
+use std::collections::{HashMap, VecDeque};
+
+/// players take the neutral cell they are on, two players on the same neutral cell leave it neutral
+pub fn claim_cells(width: i32, owners: &mut [i32], positions: &[[i32; 2]]) {
+    let mut claims = HashMap::<usize, i32>::new();
+    for (player_id, position) in positions.iter().enumerate() {
+        let index = (position[1] * width + position[0]) as usize;
+        if owners[index] >= 0 {
+            continue;
+        }
+        claims
+            .entry(index)
+            .and_modify(|id| *id = -1)
+            .or_insert(player_id as i32);
+    }
+    for (index, player_id) in claims {
+        owners[index] = player_id;
+    }
+}
+
+/// neutral cells the player captures with the cells it owns
+///
+/// - a zone is every cell (4 directions) we can reach without walking on the player cells
+/// - a zone touching the border of the grid is not enclosed
+/// - a zone with another player inside can not be captured
+/// - only neutral cells are captured, cells owned by other players stay theirs
+pub fn get_captured_cells(
+    width: i32,
+    height: i32,
+    owners: &[i32],
+    positions: &[[i32; 2]],
+    player_id: i32,
+) -> Vec<usize> {
+    let get_cell_index = |x: i32, y: i32| (y * width + x) as usize;
+    let mut zones = vec![-1; owners.len()];
+    let mut captured = Vec::new();
+    for start in 0..owners.len() {
+        if zones[start] >= 0 || owners[start] == player_id {
+            continue;
+        }
+
+        // flood fill the zone
+        let mut zone = vec![start];
+        let mut queue = VecDeque::from([start]);
+        let mut is_enclosed = true;
+        zones[start] = start as i32;
+        while let Some(index) = queue.pop_front() {
+            let x = index as i32 % width;
+            let y = index as i32 / width;
+            if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                is_enclosed = false;
+            }
+            for [nx, ny] in [[x - 1, y], [x + 1, y], [x, y - 1], [x, y + 1]] {
+                if !(0..width).contains(&nx) || !(0..height).contains(&ny) {
+                    continue;
+                }
+                let neighbor = get_cell_index(nx, ny);
+                if zones[neighbor] >= 0 || owners[neighbor] == player_id {
+                    continue;
+                }
+                zones[neighbor] = start as i32;
+                zone.push(neighbor);
+                queue.push_back(neighbor);
+            }
+        }
+
+        if !is_enclosed {
+            continue;
+        }
+        let has_other_player = positions.iter().enumerate().any(|(id, position)| {
+            id as i32 != player_id
+                && zones[get_cell_index(position[0], position[1])] == start as i32
+        });
+        if has_other_player {
+            continue;
+        }
+        captured.extend(zone.into_iter().filter(|index| owners[*index] < 0));
+    }
+    captured
+}
+
+/// applies the captures of every player, in the order of the players
+pub fn capture_cells(width: i32, height: i32, owners: &mut [i32], positions: &[[i32; 2]]) {
+    for player_id in 0..positions.len() as i32 {
+        for index in get_captured_cells(width, height, owners, positions, player_id) {
+            owners[index] = player_id;
+        }
+    }
+}
@@ -0,0 +1,105 @@
+// time left to answer a turn, CodinGame kills a bot that answers too late
+//
+// a turn starts with its first input line (see `transcript::read_line`), so the time spent reading and parsing the
+// inputs counts too, searches check `should_stop` and keep their best result so far once the budget is spent
+//
+// the `TURN_BUDGET` environment variable replaces the budget of every turn, `unlimited` or milliseconds: a bot
+// stopped by its timer answers the same on every replay (see `src/bin/replay.rs`) only when it is unlimited
+
+use crate::transcript;
+use std::env;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// the time a bot has to answer, the first turn is longer to set things up
+#[derive(Copy, Clone, Debug)]
+pub struct Budget {
+    pub first_turn: Duration,
+    pub turn: Duration,
+}
+
+impl Budget {
+    pub const fn from_millis(first_turn: u64, turn: u64) -> Self {
+        Self {
+            first_turn: Duration::from_millis(first_turn),
+            turn: Duration::from_millis(turn),
+        }
+    }
+
+    /// the budget of the given turn, counted from 1
+    pub fn get(&self, turn: i32) -> Duration {
+        if turn <= 1 {
+            self.first_turn
+        } else {
+            self.turn
+        }
+    }
+}
+
+/// most puzzles and games
+pub const DEFAULT: Budget = Budget::from_millis(1000, 100);
+pub const BACK_TO_THE_CODE: Budget = Budget::from_millis(1000, 100);
+pub const CODE_A_LA_MODE: Budget = Budget::from_millis(1000, 50);
+pub const THE_LABYRINTH: Budget = Budget::from_millis(1000, 150);
+
+/// `unlimited` or milliseconds, the budget of every turn given by `TURN_BUDGET`
+pub fn parse_budget(spec: &str) -> Option<Duration> {
+    match spec.trim() {
+        "unlimited" => Some(Duration::MAX),
+        millis => millis.parse().ok().map(Duration::from_millis),
+    }
+}
+
+fn get_budget_override() -> Option<Duration> {
+    static OVERRIDE: OnceLock<Option<Duration>> = OnceLock::new();
+    *OVERRIDE.get_or_init(|| {
+        let spec = env::var("TURN_BUDGET").ok()?;
+        let budget = parse_budget(&spec);
+        if budget.is_none() {
+            eprintln!("TURN_BUDGET: unknown budget {}", spec);
+        }
+        budget
+    })
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct TurnTimer {
+    start: Instant,
+    limit: Duration,
+}
+
+impl TurnTimer {
+    /// a timer for the current turn, started when its first line was read (now if nothing was read yet),
+    /// `TURN_BUDGET` replaces the limit
+    pub fn start(limit: Duration) -> Self {
+        Self {
+            start: transcript::get_turn_start().unwrap_or_else(Instant::now),
+            limit: get_budget_override().unwrap_or(limit),
+        }
+    }
+
+    pub fn for_turn(budget: Budget, turn: i32) -> Self {
+        Self::start(budget.get(turn))
+    }
+
+    /// never runs out, for benchmarks and local tools
+    pub fn unlimited() -> Self {
+        Self {
+            start: Instant::now(),
+            limit: Duration::MAX,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.limit.saturating_sub(self.elapsed())
+    }
+
+    /// less than `margin` is left, the time to stop searching and answer with what we have
+    pub fn should_stop(&self, margin: Duration) -> bool {
+        self.remaining() <= margin
+    }
+}
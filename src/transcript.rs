@@ -0,0 +1,104 @@
+// stdin/stdout of a bot, recorded into a transcript to replay a game later (see `src/bin/replay.rs`)
+//
+// recording starts when the `TRANSCRIPT` environment variable or the `--transcript FILE` argument names a file,
+// `-` records on stderr (the only output kept online, other debug lines are ignored by the replay)
+//
+// a transcript is a text file:
+// - `# ...` comments (the command line of the bot)
+// - `> line` a line the bot read
+// - `< micros line` a line the bot wrote, `micros` after the first line of its turn
+
+use std::env;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+/// prints the answer of the turn like `println!`, recorded in the transcript
+#[macro_export]
+macro_rules! write_answer {
+    ($($arg:tt)*) => {
+        $crate::transcript::write_line(format_args!($($arg)*))
+    };
+}
+
+struct Recorder {
+    file: Box<dyn Write + Send>,
+}
+
+impl Recorder {
+    pub fn create(path: &str) -> io::Result<Self> {
+        let mut file: Box<dyn Write + Send> = if path == "-" {
+            Box::new(io::stderr())
+        } else {
+            Box::new(BufWriter::new(File::create(path)?))
+        };
+        writeln!(file, "# {}", env::args().collect::<Vec<_>>().join(" "))?;
+        Ok(Self { file })
+    }
+}
+
+/// the file named by `TRANSCRIPT` or `--transcript FILE`
+fn get_path() -> Option<String> {
+    if let Ok(path) = env::var("TRANSCRIPT") {
+        return Some(path);
+    }
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--transcript" {
+            return args.next();
+        }
+    }
+    None
+}
+
+/// the recorder, created on first use when a transcript file is asked for
+fn get_recorder() -> Option<&'static Mutex<Recorder>> {
+    static RECORDER: OnceLock<Option<Mutex<Recorder>>> = OnceLock::new();
+    RECORDER
+        .get_or_init(|| {
+            let path = get_path()?;
+            match Recorder::create(&path) {
+                Ok(recorder) => Some(Mutex::new(recorder)),
+                Err(e) => {
+                    eprintln!("can not record the transcript in {}: {}", path, e);
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+/// the first line of the turn arrived at, until the answer is written
+static TURN_START: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// when the first line of the current turn was read, `None` once answered
+pub fn get_turn_start() -> Option<Instant> {
+    *TURN_START.lock().unwrap()
+}
+
+/// reads a line of stdin like `io::stdin().read_line`, the end of input is an empty line
+pub fn read_line(line: &mut String) -> usize {
+    let count = io::stdin().read_line(line).unwrap();
+    TURN_START.lock().unwrap().get_or_insert_with(Instant::now);
+    if let Some(recorder) = get_recorder() {
+        let mut recorder = recorder.lock().unwrap();
+        let _ = writeln!(recorder.file, "> {}", line.trim_end_matches(['\r', '\n']));
+    }
+    count
+}
+
+/// writes a line on stdout, the answer ends the turn
+pub fn write_line(line: impl Display) {
+    let line = line.to_string();
+    let turn_start = TURN_START.lock().unwrap().take();
+    // recorded first, the bot may be killed as soon as its last answer is read
+    if let Some(recorder) = get_recorder() {
+        let mut recorder = recorder.lock().unwrap();
+        let micros = turn_start.map_or(0, |start| start.elapsed().as_micros());
+        let _ = writeln!(recorder.file, "< {} {}", micros, line);
+        let _ = recorder.file.flush();
+    }
+    println!("{}", line);
+}
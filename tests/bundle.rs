@@ -0,0 +1,96 @@
+// every bot bundled into a single file compiles on its own with rustc, like CodinGame does, without its debug lines
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const PUZZLES: [&str; 6] = [
+    "death-first-search-episode-1",
+    "death-first-search-episode-2",
+    "dont-panic-episode-1",
+    "shadows-of-the-knight-episode-1",
+    "the-labyrinth",
+    "winamax-battle",
+];
+const GAMES: [&str; 3] = ["back-to-the-code", "code-a-la-mode", "code-vs-zombies"];
+/// the characters CodinGame accepts in a submission
+const MAX_SOURCE_LENGTH: usize = 100_000;
+
+/// bundles the bot and compiles it in the directory, returns the bundled source
+fn bundle_and_compile(bot: &str, dir: &Path) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_bundle"))
+        .arg(bot)
+        .output()
+        .expect("can not run the bundler");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let source = String::from_utf8(output.stdout).expect("the bundle is text");
+    let path = dir.join(format!("{}.rs", bot));
+    std::fs::write(&path, &source).expect("the bundle is written");
+    let output = Command::new("rustc")
+        .args(["--edition", "2021", "-D", "warnings", "-o"])
+        .arg(dir.join(bot))
+        .arg(&path)
+        .output()
+        .expect("can not run rustc");
+    assert!(
+        output.status.success(),
+        "{} does not compile:\n{}",
+        bot,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    source
+}
+
+/// what the bot prints on stderr for the input
+fn get_stderr(bot: &Path, input: &str) -> String {
+    let mut child = Command::new(bot)
+        .env_remove("LOG")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("can not run the bot");
+    // the bot stops at the end of the input
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+    let output = child.wait_with_output().expect("the bot ends");
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+#[test]
+fn bundled_bots_compile_and_pass_the_cases() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bundle");
+    std::fs::create_dir_all(&dir).expect("the bundle directory is created");
+    std::thread::scope(|scope| {
+        for bot in PUZZLES.iter().chain(GAMES.iter()) {
+            let dir = &dir;
+            scope.spawn(move || {
+                let source = bundle_and_compile(bot, dir);
+                assert!(source.chars().count() <= MAX_SOURCE_LENGTH, "{}", bot);
+                assert!(!source.contains("#[cfg(test)]"), "{}", bot);
+            });
+        }
+    });
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cases"))
+        .arg("--bots")
+        .arg(&dir)
+        .arg("--cases")
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cases"))
+        .args(PUZZLES)
+        .output()
+        .expect("can not run the cases runner");
+    let report = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", report);
+
+    // the position of Batman and the part of the building left on a debug line
+    let input = "10 10\n6\n2 5\nUR\n";
+    let debug_line = "/min_x:";
+    let built = Path::new(env!("CARGO_BIN_EXE_shadows-of-the-knight-episode-1"));
+    let bundled = dir.join("shadows-of-the-knight-episode-1");
+    assert!(get_stderr(built, input).contains(debug_line));
+    assert!(!get_stderr(&bundled, input).contains(debug_line));
+}
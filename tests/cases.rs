@@ -0,0 +1,44 @@
+// the cases of tests/cases, run by the `cases` runner against the puzzle bots
+
+use std::process::Command;
+
+fn run_cases(puzzle: &str) {
+    let output = Command::new(env!("CARGO_BIN_EXE_cases"))
+        .arg("--cases")
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cases"))
+        .arg(puzzle)
+        .output()
+        .expect("can not run the cases runner");
+    let report = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", report);
+}
+
+#[test]
+fn winamax_battle() {
+    run_cases("winamax-battle");
+}
+
+#[test]
+fn shadows_of_the_knight_episode_1() {
+    run_cases("shadows-of-the-knight-episode-1");
+}
+
+#[test]
+fn death_first_search_episode_1() {
+    run_cases("death-first-search-episode-1");
+}
+
+#[test]
+fn death_first_search_episode_2() {
+    run_cases("death-first-search-episode-2");
+}
+
+#[test]
+fn dont_panic_episode_1() {
+    run_cases("dont-panic-episode-1");
+}
+
+#[test]
+fn the_labyrinth() {
+    run_cases("the-labyrinth");
+}
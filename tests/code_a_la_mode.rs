@@ -0,0 +1,96 @@
+// the code-a-la-mode bot cooks with the scripted server of the referee, it should not prepare an order the server
+// serves first nor get stuck in front of it, in the default kitchen and in one where every crate is at the end of
+// a dead-end corridor
+
+use std::process::Command;
+
+/// turns in a row a chef may try to move without moving, more is a deadlock
+const MAX_STUCK_TURNS: i32 = 10;
+
+/// what the referee prints for a chef
+struct ChefStats {
+    delivered: i32,
+    duplicated_orders: i32,
+    stuck_turns: i32,
+}
+
+fn play(kitchen: Option<&str>, seed: u64, with_tarts: bool) -> Vec<ChefStats> {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_code-a-la-mode-referee"));
+    command.arg("--seed").arg(seed.to_string());
+    if let Some(kitchen) = kitchen {
+        command.arg("--kitchen").arg(format!(
+            "{}/tests/kitchens/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            kitchen
+        ));
+    }
+    if with_tarts {
+        command.arg("--tarts");
+    }
+    let output = command
+        .arg(env!("CARGO_BIN_EXE_code-a-la-mode"))
+        .arg("scripted:server")
+        .output()
+        .expect("can not run the referee");
+    let report = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", report);
+    // chef 0: 21 delivered, 0 duplicated orders, 0 wasted dishes, stuck 4 turns in a row at most
+    let stats = report
+        .lines()
+        .filter(|line| line.starts_with("chef "))
+        .map(|line| {
+            let numbers = line
+                .split_whitespace()
+                .filter_map(|word| word.trim_end_matches(':').parse().ok())
+                .collect::<Vec<i32>>();
+            ChefStats {
+                delivered: numbers[1],
+                duplicated_orders: numbers[2],
+                stuck_turns: numbers[4],
+            }
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(stats.len(), 2, "{}", report);
+    stats
+}
+
+fn check(kitchen: Option<&str>, with_tarts: bool) {
+    for seed in 1..=5 {
+        let stats = play(kitchen, seed, with_tarts);
+        assert!(stats[0].delivered > 0, "seed {}: nothing delivered", seed);
+        assert_eq!(
+            stats[0].duplicated_orders, 0,
+            "seed {}: the server served our order",
+            seed
+        );
+        for (chef, stats) in stats.iter().enumerate() {
+            assert!(
+                stats.stuck_turns <= MAX_STUCK_TURNS,
+                "seed {}: chef {} was stuck {} turns in a row",
+                seed,
+                chef,
+                stats.stuck_turns
+            );
+        }
+    }
+}
+
+#[test]
+fn cooks_with_the_server() {
+    check(None, false);
+}
+
+#[test]
+fn cooks_tarts_with_the_server() {
+    check(None, true);
+}
+
+#[test]
+fn cooks_with_the_server_in_a_dead_end() {
+    check(Some("dead-end.txt"), false);
+}
+
+#[test]
+fn cooks_tarts_with_the_server_in_a_dead_end() {
+    check(Some("dead-end.txt"), true);
+}
@@ -0,0 +1,48 @@
+// the generated mazes of The Labyrinth can be played: walled in, Kirk can get to the command room and back in time
+
+use codinggame_backtocode_rust::maze;
+use codinggame_backtocode_rust::random::Random;
+
+/// the position of the first cell holding the character
+fn find(maze: &[Vec<char>], target: char) -> (i32, i32) {
+    maze.iter()
+        .enumerate()
+        .find_map(|(y, row)| {
+            let x = row.iter().position(|cell| *cell == target)?;
+            Some((x as i32, y as i32))
+        })
+        .unwrap()
+}
+
+#[test]
+fn generated_mazes_can_be_played() {
+    let mut random = Random::new(3);
+    for (rows, columns) in [(11, 21), (15, 30), (21, 51)] {
+        let (maze, alarm) = maze::generate(&mut random, rows, columns);
+        assert_eq!(maze.len(), rows as usize);
+        assert!(maze.iter().all(|row| row.len() == columns as usize));
+        assert!(maze.first().unwrap().iter().all(|cell| *cell == '#'));
+        assert!(maze.last().unwrap().iter().all(|cell| *cell == '#'));
+        assert!(maze
+            .iter()
+            .all(|row| row[0] == '#' && row[row.len() - 1] == '#'));
+        let cells = maze.iter().flatten().collect::<String>();
+        assert_eq!(
+            (cells.matches('T').count(), cells.matches('C').count()),
+            (1, 1)
+        );
+
+        let (start, command) = (find(&maze, 'T'), find(&maze, 'C'));
+        let distance = maze::get_distances(&maze, start)[command.1 as usize][command.0 as usize];
+        assert_eq!(alarm, distance + 5);
+    }
+}
+
+#[test]
+fn same_seed_same_maze() {
+    let (mut a, mut b) = (Random::new(8), Random::new(8));
+    assert_eq!(
+        maze::generate(&mut a, 15, 30),
+        maze::generate(&mut b, 15, 30)
+    );
+}
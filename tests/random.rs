@@ -0,0 +1,54 @@
+// the seeded generator gives the same numbers on every machine and every version, seeded games depend on it
+
+use codinggame_backtocode_rust::random::Random;
+
+#[test]
+fn same_seed_same_numbers() {
+    let mut random = Random::new(1);
+    assert_eq!(
+        [random.next_u64(), random.next_u64(), random.next_u64()],
+        [
+            15860402102123842989,
+            7273575876580499574,
+            8865281517519135030
+        ]
+    );
+    let (mut a, mut b) = (Random::new(42), Random::new(42));
+    assert!((0..1000).all(|_| a.next_u64() == b.next_u64()));
+    let (mut a, mut b) = (Random::new(1), Random::new(2));
+    assert!((0..10).any(|_| a.next_u64() != b.next_u64()));
+}
+
+#[test]
+fn ranges() {
+    let mut random = Random::new(7);
+    for _ in 0..1000 {
+        assert!((-3..5).contains(&random.range(-3, 5)));
+        assert!((0. ..1.).contains(&random.next_f64()));
+        assert!((2. ..3.).contains(&random.range_f64(2., 3.)));
+    }
+    assert!((0..1000).all(|_| !random.chance(0.)));
+    assert!((0..1000).all(|_| random.chance(1.)));
+}
+
+#[test]
+fn shuffle() {
+    let mut random = Random::new(1);
+    let mut items = (0..10).collect::<Vec<_>>();
+    random.shuffle(&mut items);
+    assert_eq!(items, [5, 1, 4, 7, 8, 2, 3, 6, 0, 9]);
+}
+
+#[test]
+fn choices() {
+    let mut random = Random::new(3);
+    assert_eq!(random.choose::<i32>(&[]), None);
+    assert_eq!(random.choose(&[4]), Some(&4));
+    assert_eq!(random.choose_weighted(&[0., -1.]), None);
+    let mut counts = [0; 3];
+    for _ in 0..3000 {
+        counts[random.choose_weighted(&[1., 0., 2.]).unwrap()] += 1;
+    }
+    assert_eq!(counts[1], 0);
+    assert!((800..1200).contains(&counts[0]), "{:?}", counts);
+}
@@ -0,0 +1,37 @@
+// a game recorded against the referee replays with the same answers, the turn timers of the bot never run out
+// during the replay nor during this recording
+
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn a_recorded_game_replays_the_same() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("code-a-la-mode-transcript.txt");
+    let bot = format!(
+        "{} --transcript {}",
+        env!("CARGO_BIN_EXE_code-a-la-mode"),
+        path.display()
+    );
+    let output = Command::new(env!("CARGO_BIN_EXE_code-a-la-mode-referee"))
+        .args(["--seed", "3", "--rounds", "1"])
+        .arg(&bot)
+        .arg("scripted:server")
+        .env("TURN_BUDGET", "unlimited")
+        .output()
+        .expect("can not run the referee");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_replay"))
+        .arg(&path)
+        .arg(env!("CARGO_BIN_EXE_code-a-la-mode"))
+        .env_remove("TURN_BUDGET")
+        .output()
+        .expect("can not run the replay");
+    let report = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", report);
+    assert!(report.contains(" turns, 0 differences"), "{}", report);
+}
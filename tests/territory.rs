@@ -0,0 +1,44 @@
+// the capture rules the Back to the Code bot and its referee share
+
+use codinggame_backtocode_rust::territory;
+
+/// the owners of a board written one row per line, `.` for a neutral cell
+fn parse_owners(rows: &[&str]) -> Vec<i32> {
+    rows.iter()
+        .flat_map(|row| row.chars())
+        .map(|c| c.to_digit(10).map_or(-1, |id| id as i32))
+        .collect()
+}
+
+#[test]
+fn enclosed_neutral_cells_are_captured() {
+    let owners = parse_owners(&["00000", "0..10", "00000", "....."]);
+    let positions = [[0, 0], [4, 3]];
+    // the cell of player 1 inside the ring stays its own
+    assert_eq!(
+        territory::get_captured_cells(5, 4, &owners, &positions, 0),
+        vec![6, 7]
+    );
+    assert!(territory::get_captured_cells(5, 4, &owners, &positions, 1).is_empty());
+}
+
+#[test]
+fn zones_on_the_border_or_with_another_player_are_not_captured() {
+    let owners = parse_owners(&["00.00", "0...0", "00000"]);
+    assert!(territory::get_captured_cells(5, 3, &owners, &[[0, 0], [4, 2]], 0).is_empty());
+    let owners = parse_owners(&["00000", "0...0", "00000", "....."]);
+    assert!(territory::get_captured_cells(5, 4, &owners, &[[0, 0], [2, 1]], 0).is_empty());
+}
+
+#[test]
+fn claims_and_captures_of_a_round() {
+    let mut owners = parse_owners(&["000..", "0.0..", "0....", "....."]);
+    // both players enter the same neutral cell, it stays neutral
+    territory::claim_cells(5, &mut owners, &[[3, 0], [3, 0]]);
+    assert_eq!(owners[3], -1);
+    // player 0 closes its ring around the cell 1,1
+    let positions = [[1, 2], [4, 3]];
+    territory::claim_cells(5, &mut owners, &positions);
+    territory::capture_cells(5, 4, &mut owners, &positions);
+    assert_eq!(owners, parse_owners(&["000..", "000..", "00...", "....1"]));
+}
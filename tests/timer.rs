@@ -0,0 +1,25 @@
+// `TURN_BUDGET` replaces the budget of every turn, the replay makes the timers unlimited with it
+
+use codinggame_backtocode_rust::timer::{self, TurnTimer};
+use std::time::Duration;
+
+#[test]
+fn budgets_are_unlimited_or_milliseconds() {
+    assert_eq!(timer::parse_budget("unlimited"), Some(Duration::MAX));
+    assert_eq!(timer::parse_budget("50"), Some(Duration::from_millis(50)));
+    assert_eq!(
+        timer::parse_budget(" 50\n"),
+        Some(Duration::from_millis(50))
+    );
+    assert_eq!(timer::parse_budget("50ms"), None);
+    assert_eq!(timer::parse_budget(""), None);
+}
+
+#[test]
+fn the_environment_replaces_the_budget_of_the_game() {
+    // the only test reading it, it is read once
+    std::env::set_var("TURN_BUDGET", "unlimited");
+    let timer = TurnTimer::for_turn(timer::CODE_A_LA_MODE, 2);
+    std::thread::sleep(Duration::from_millis(60));
+    assert!(!timer.should_stop(Duration::from_secs(3600)));
+}